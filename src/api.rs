@@ -4,11 +4,18 @@
 use anyhow::{Result, Context};
 use reqwest::{Client, Response};
 use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Mutex;
 
+use crate::archive::RawResponse;
 use crate::config::ModuleConfig;
 use crate::types::XsiamObject;
+use crate::script_code;
 use crate::zip_safety;
-use crate::modules::{ContentTypeDefinition, PullStrategy};
+use crate::modules::{ContentTypeDefinition, IdentityField, PullStrategy};
+
+/// Management audit log endpoint, relative to the tenant
+const MANAGEMENT_AUDIT_LOGS_PATH: &str = "/public_api/v1/audits/management_logs";
 
 pub struct ModuleClient {
     client: Client,
@@ -16,6 +23,10 @@ pub struct ModuleClient {
     api_key: String,
     api_key_id: String,
     base_api_path: String,
+    /// Raw response bodies per content type, kept when responses are archived
+    raw_responses: Option<Mutex<Vec<RawResponse>>>,
+    /// Content types whose last pull skipped objects that failed to load
+    incomplete_pulls: Mutex<Vec<String>>,
 }
 
 impl ModuleClient {
@@ -27,16 +38,46 @@ impl ModuleClient {
             api_key: config.api_key,
             api_key_id: config.api_key_id,
             base_api_path: base_api_path.to_string(),
+            raw_responses: None,
+            incomplete_pulls: Mutex::new(Vec::new()),
         }
     }
 
-    // Future push feature - create or update objects on platform
-    #[allow(dead_code)]
+    /// Whether the last pull of a content type returned every object the platform listed
+    /// Objects missing from an incomplete pull may still exist, so they must not be treated as deleted.
+    pub fn pull_was_complete(&self, content_type: &str) -> bool {
+        self.incomplete_pulls.lock()
+            .map(|incomplete| !incomplete.iter().any(|name| name == content_type))
+            .unwrap_or(false)
+    }
+
+    /// Keep the raw body of every collection and script response so the pull can be archived
+    pub fn with_response_archive(mut self) -> Self {
+        self.raw_responses = Some(Mutex::new(Vec::new()));
+        self
+    }
+
+    /// Raw responses recorded since the last call, in request order
+    pub fn take_raw_responses(&self) -> Vec<RawResponse> {
+        self.raw_responses.as_ref()
+            .and_then(|raw| raw.lock().ok().map(|mut raw| std::mem::take(&mut *raw)))
+            .unwrap_or_default()
+    }
+
+    /// Create or update an object on the platform
     pub async fn create_or_update_object(&self, object: &XsiamObject, content_def: &ContentTypeDefinition) -> Result<()> {
-        let url = format!("https://{}{}/{}", self.fqdn, self.base_api_path, content_def.get_endpoint);
+        let endpoint = content_def.write_endpoint
+            .ok_or_else(|| anyhow::anyhow!("Writing {} to the platform is not supported", content_def.name))?;
+        let url = format!("https://{}{}/{}", self.fqdn, self.base_api_path, endpoint);
 
-        // Convert XsiamObject to API format by extracting the content field
-        let api_payload = object.content.clone();
+        // Insert endpoints take a list of objects, matched on their ID fields to update
+        let mut item = serde_json::Map::new();
+        if let Some(name) = &object.name {
+            item.insert("name".to_string(), Value::String(name.clone()));
+        }
+        item.insert("description".to_string(), Value::String(object.description.clone()));
+        item.extend(object.content.iter().map(|(key, value)| (key.clone(), value.clone())));
+        let api_payload = serde_json::json!({ "request_data": [item] });
 
         let response = self.client
             .post(&url)
@@ -52,16 +93,79 @@ impl ModuleClient {
         self.handle_response(response, &format!("create/update {}", object.content_type)).await
     }
 
-    // Future delete feature - remove objects from platform
-    #[allow(dead_code)]
+    /// Fetch management audit log records inserted within a time window
+    pub async fn fetch_management_audit_logs(&self, since: chrono::DateTime<chrono::Utc>, until: chrono::DateTime<chrono::Utc>) -> Result<Vec<Value>> {
+        // The audit log is a tenant-wide public API, whatever the module's own base path
+        let url = format!("https://{}{MANAGEMENT_AUDIT_LOGS_PATH}", self.fqdn);
+        let page_size = 100;
+        let mut records = Vec::new();
+
+        loop {
+            let request_data = serde_json::json!({
+                "request_data": {
+                    "filters": [
+                        {"field": "timestamp", "operator": "gte", "value": since.timestamp_millis()},
+                        {"field": "timestamp", "operator": "lte", "value": until.timestamp_millis()}
+                    ],
+                    "search_from": records.len(),
+                    "search_to": records.len() + page_size,
+                    "sort": {"field": "timestamp", "keyword": "asc"}
+                }
+            });
+
+            let response = self.client
+                .post(&url)
+                .header("x-xdr-auth-id", &self.api_key_id)
+                .header("Authorization", &self.api_key)
+                .header("Content-Type", "application/json")
+                .header("Accept", "application/json")
+                .json(&request_data)
+                .send()
+                .await
+                .with_context(|| format!("Failed to send audit log request to {url}"))?;
+
+            if !response.status().is_success() {
+                return Err(anyhow::anyhow!("Audit log request failed with status: {}", response.status()));
+            }
+
+            let json: Value = response.json().await.context("Failed to parse audit log response")?;
+            let batch = json.get("reply")
+                .and_then(|reply| reply.get("data"))
+                .and_then(|data| data.as_array())
+                .cloned()
+                .unwrap_or_default();
+            let total = json.get("reply")
+                .and_then(|reply| reply.get("total_count"))
+                .and_then(|v| v.as_u64())
+                .unwrap_or(0) as usize;
+
+            let batch_len = batch.len();
+            records.extend(batch);
+            if batch_len < page_size || records.len() >= total {
+                break;
+            }
+        }
+
+        Ok(records)
+    }
+
+    /// Remove an object from the platform
     pub async fn delete_object(&self, object: &XsiamObject, content_def: &crate::modules::ContentTypeDefinition) -> Result<()> {
-        let url = format!("https://{}{}/{}", self.fqdn, self.base_api_path, content_def.get_endpoint);
+        let endpoint = content_def.delete_endpoint
+            .ok_or_else(|| anyhow::anyhow!("Deleting {} on the platform is not supported", content_def.name))?;
+        let url = format!("https://{}{}/{}", self.fqdn, self.base_api_path, endpoint);
 
-        // Build request data using the content type's ID field dynamically
-        let mut request_map = serde_json::Map::new();
-        request_map.insert(content_def.id_field.to_string(), serde_json::json!(&object.id));
+        // Select the object with a filter on the content type's ID field, typed as the platform gives it
+        let id_value = match content_def.id_fields.first() {
+            Some(IdentityField::Int(_)) => object.id.parse::<i64>().map(Value::from).unwrap_or_else(|_| Value::String(object.id.clone())),
+            _ => Value::String(object.id.clone()),
+        };
         let request_data = serde_json::json!({
-            "request_data": request_map
+            "request_data": {
+                "filters": [
+                    {"field": content_def.id_field(), "operator": "EQ", "value": id_value}
+                ]
+            }
         });
 
         let response = self.client
@@ -331,7 +435,7 @@ impl ModuleClient {
         // Find the specific object by ID
         for object in objects {
             // Check both id field and the content type's id_field in content
-            let id_field_match = object.content.get(content_def.id_field)
+            let id_field_match = object.content.get(content_def.id_field())
                 .and_then(|v| {
                     if v.is_string() {
                         v.as_str().map(|s| s.to_string())
@@ -355,7 +459,6 @@ impl ModuleClient {
 
 
     // Helper for API response handling
-    #[allow(dead_code)]
     async fn handle_response(&self, response: Response, operation: &str) -> Result<()> {
         if response.status().is_success() {
             Ok(())
@@ -399,7 +502,7 @@ impl ModuleClient {
 
         // Build request data using the content type's ID field dynamically
         let mut request_map = serde_json::Map::new();
-        request_map.insert(content_def.id_field.to_string(), serde_json::json!(id));
+        request_map.insert(content_def.id_field().to_string(), serde_json::json!(id));
         let request_data = serde_json::json!({
             "request_data": request_map
         });
@@ -589,7 +692,8 @@ impl ModuleClient {
     }
 
     #[allow(dead_code)]
-    fn parse_api_response(&self, json: &Value, content_type: &str) -> Result<Vec<XsiamObject>> {
+    fn parse_api_response(&self, json: &Value, content_def: &ContentTypeDefinition) -> Result<Vec<XsiamObject>> {
+        let content_type = content_def.name;
         let mut objects = Vec::new();
 
         // Handle different response formats based on content type
@@ -664,7 +768,7 @@ impl ModuleClient {
         };
 
         for item in items.iter() {
-            let object = XsiamObject::from_api_response(item, content_type)?;
+            let object = XsiamObject::from_api_response(item, content_def)?;
             objects.push(object);
         }
 
@@ -724,7 +828,7 @@ impl ModuleClient {
             return Err(anyhow::anyhow!("API request failed with status: {}", response.status()));
         }
         
-        let json: Value = response.json().await.context("Failed to parse JSON response")?;
+        let json = self.read_json_response(response, content_def.name, None).await?;
         self.extract_items_from_response(&json, content_def)
     }
     
@@ -753,7 +857,7 @@ impl ModuleClient {
                 return Err(anyhow::anyhow!("API request failed with status: {}", response.status()));
             }
             
-            let json: Value = response.json().await.context("Failed to parse JSON response")?;
+            let json = self.read_json_response(response, content_def.name, None).await?;
             let objects = self.extract_items_from_response(&json, content_def)?;
             
             // Check for hasNext field to determine if more pages exist
@@ -803,7 +907,7 @@ impl ModuleClient {
                 return Err(anyhow::anyhow!("API request failed with status: {}", response.status()));
             }
 
-            let json: Value = response.json().await.context("Failed to parse JSON response")?;
+            let json = self.read_json_response(response, content_def.name, None).await?;
             let objects = self.extract_items_from_response(&json, content_def)?;
 
             let batch_size = objects.len();
@@ -845,6 +949,9 @@ impl ModuleClient {
             .ok_or_else(|| anyhow::anyhow!("Expected array at path {metadata_response_path}"))?;
         
         let mut script_objects = Vec::new();
+        if let Ok(mut incomplete) = self.incomplete_pulls.lock() {
+            incomplete.retain(|name| name != content_def.name);
+        }
         
         for script_meta in scripts_list {
             let script_name = script_meta
@@ -853,7 +960,7 @@ impl ModuleClient {
                 .ok_or_else(|| anyhow::anyhow!("Script missing name field"))?;
             
             let script_id = script_meta
-                .get(content_def.id_field)
+                .get(content_def.id_field())
                 .and_then(|id| id.as_str())
                 .unwrap_or(script_name)
                 .to_string();
@@ -866,7 +973,7 @@ impl ModuleClient {
                         if let Ok(json_value) = serde_json::to_value(&yaml_value) {
                             if let Some(obj) = json_value.as_object() {
                                 for (key, value) in obj {
-                                    if key != "name" && key != "description" && key != content_def.id_field {
+                                    if key != "name" && key != "description" && key != content_def.id_field() {
                                         content_map.insert(key.clone(), value.clone());
                                     }
                                 }
@@ -874,7 +981,7 @@ impl ModuleClient {
                         }
                     }
                     
-                    content_map.insert(content_def.id_field.to_string(), serde_json::json!(script_id.clone()));
+                    content_map.insert(content_def.id_field().to_string(), serde_json::json!(script_id.clone()));
                     
                     for (key, value) in script_meta.as_object().unwrap_or(&serde_json::Map::new()) {
                         if key != "name" && key != "description" {
@@ -955,6 +1062,46 @@ impl ModuleClient {
     
     /// Pull script code - two-step process (list scripts + fetch code by UID)
     async fn pull_script_code(&self, content_def: &ContentTypeDefinition, list_endpoint: &str, code_endpoint: &str, list_response_path: &str, uid_field: &str) -> Result<Vec<XsiamObject>> {
+        let scripts_list = self.list_scripts(content_def, list_endpoint, list_response_path).await?;
+
+        let mut script_objects = Vec::new();
+        if let Ok(mut incomplete) = self.incomplete_pulls.lock() {
+            incomplete.retain(|name| name != content_def.name);
+        }
+        
+        for script_meta in &scripts_list {
+            let script_uid = Self::script_uid(script_meta, uid_field)?;
+            match self.get_script_code(content_def, code_endpoint, script_uid).await {
+                Ok(script_code) => script_objects.push(Self::script_object(content_def, script_meta, uid_field, Some(script_code))?),
+                Err(e) => {
+                    let script_name = script_meta.get("name").and_then(|n| n.as_str()).unwrap_or(script_uid);
+                    eprintln!("Warning: Failed to get code for script '{script_name}': {e}");
+                    if let Ok(mut incomplete) = self.incomplete_pulls.lock() {
+                        incomplete.push(content_def.name.to_string());
+                    }
+                }
+            }
+        }
+        
+        Ok(script_objects)
+    }
+
+    /// List content without fetching per-object payloads such as script code
+    /// Enough to count objects and read their modification times; other strategies pull as usual.
+    pub async fn list_content_type(&self, content_def: &ContentTypeDefinition) -> Result<Vec<XsiamObject>> {
+        match &content_def.pull_strategy {
+            PullStrategy::ScriptCode { list_endpoint, list_response_path, uid_field, .. } => {
+                self.list_scripts(content_def, list_endpoint, list_response_path).await?
+                    .iter()
+                    .map(|script_meta| Self::script_object(content_def, script_meta, uid_field, None))
+                    .collect()
+            }
+            _ => self.pull_content_type(content_def).await,
+        }
+    }
+
+    /// Fetch the script list, without code
+    async fn list_scripts(&self, content_def: &ContentTypeDefinition, list_endpoint: &str, list_response_path: &str) -> Result<Vec<Value>> {
         let list_url = format!("https://{}{}/{}", self.fqdn, self.base_api_path, list_endpoint);
         
         let response = self.client
@@ -972,80 +1119,89 @@ impl ModuleClient {
             return Err(anyhow::anyhow!("API request failed with status: {}", response.status()));
         }
         
-        let json_response: Value = response.json().await.context("Failed to parse API response as JSON")?;
-        
-        let scripts_list = self.extract_value_by_path(&json_response, list_response_path)?
+        let json_response = self.read_json_response(response, content_def.name, None).await?;
+        self.scripts_from_list(&json_response, list_response_path)
+    }
+
+    fn scripts_from_list(&self, json_response: &Value, list_response_path: &str) -> Result<Vec<Value>> {
+        self.extract_value_by_path(json_response, list_response_path)?
             .as_array()
-            .ok_or_else(|| anyhow::anyhow!("Expected array at path {list_response_path}"))?;
-        
-        let mut script_objects = Vec::new();
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("Expected array at path {list_response_path}"))
+    }
+
+    fn script_uid<'a>(script_meta: &'a Value, uid_field: &str) -> Result<&'a str> {
+        script_meta
+            .get(uid_field)
+            .and_then(|uid| uid.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Script missing {uid_field} field"))
+    }
+
+    /// Build a script object from its list entry, with the code when it was fetched
+    fn script_object(content_def: &ContentTypeDefinition, script_meta: &Value, uid_field: &str, script_code: Option<String>) -> Result<XsiamObject> {
+        let script_uid = Self::script_uid(script_meta, uid_field)?;
+        let script_name = script_meta
+            .get("name")
+            .and_then(|n| n.as_str())
+            .unwrap_or(script_uid);
+
+        let mut content_map = std::collections::HashMap::new();
+        let mut metadata = crate::types::ObjectMetadata::default();
+        if let Some(script_code) = script_code {
+            metadata.additional.insert(
+                script_code::CODE_HASH_KEY.to_string(),
+                serde_json::json!(script_code::sha256_hex(script_code.as_bytes())),
+            );
+            // Store the script code exactly as the platform returned it
+            content_map.insert("code".to_string(), serde_json::json!(script_code));
+        }
         
-        for script_meta in scripts_list {
-            let script_uid = script_meta
-                .get(uid_field)
-                .and_then(|uid| uid.as_str())
-                .ok_or_else(|| anyhow::anyhow!("Script missing {uid_field} field"))?;
-            
-            let script_name = script_meta
-                .get("name")
-                .and_then(|n| n.as_str())
-                .unwrap_or(script_uid);
-            
-            match self.get_script_code(code_endpoint, script_uid).await {
-                Ok(script_code) => {
-                    let mut content_map = std::collections::HashMap::new();
-                    
-                    // Store the script code with newlines properly converted
-                    content_map.insert("code".to_string(), serde_json::json!(script_code));
-                    
-                    // Add all metadata fields except name, description, and uid
-                    for (key, value) in script_meta.as_object().unwrap_or(&serde_json::Map::new()) {
-                        if key != "name" && key != "description" && key != uid_field {
-                            content_map.insert(key.clone(), value.clone());
-                        }
-                    }
-                    
-                    let mut metadata = crate::types::ObjectMetadata::default();
-                    if let Some(created_by) = script_meta.get("created_by").and_then(|v| v.as_str()) {
-                        metadata.created_by = created_by.to_string();
-                    }
-                    if let Some(modification_date) = script_meta.get("modification_date").and_then(|v| v.as_i64()) {
-                        let seconds = if modification_date > 10000000000 {
-                            modification_date / 1000
-                        } else {
-                            modification_date
-                        };
-                        metadata.updated_at = chrono::DateTime::from_timestamp(seconds, 0);
-                    }
-                    
-                    let description = script_meta
-                        .get("description")
-                        .and_then(|d| d.as_str())
-                        .unwrap_or("")
-                        .to_string();
-                    
-                    let xsiam_obj = XsiamObject {
-                        id: script_uid.to_string(),
-                        name: Some(script_name.to_string()),
-                        description,
-                        content_type: content_def.name.to_string(),
-                        metadata,
-                        tenant_id: None,
-                        content: content_map,
-                    };
-                    script_objects.push(xsiam_obj);
-                }
-                Err(e) => {
-                    eprintln!("Warning: Failed to get code for script '{script_name}': {e}");
-                }
+        // Add all metadata fields except name, description, and uid
+        for (key, value) in script_meta.as_object().unwrap_or(&serde_json::Map::new()) {
+            if key != "name" && key != "description" && key != uid_field {
+                content_map.insert(key.clone(), value.clone());
             }
         }
         
-        Ok(script_objects)
+        if let Some(created_by) = script_meta.get("created_by").and_then(|v| v.as_str()) {
+            metadata.created_by = created_by.to_string();
+        }
+        if let Some(modification_date) = script_meta.get("modification_date").and_then(|v| v.as_i64()) {
+            let seconds = if modification_date > 10000000000 {
+                modification_date / 1000
+            } else {
+                modification_date
+            };
+            metadata.updated_at = chrono::DateTime::from_timestamp(seconds, 0);
+        }
+        
+        let description = script_meta
+            .get("description")
+            .and_then(|d| d.as_str())
+            .unwrap_or("")
+            .to_string();
+        
+        Ok(XsiamObject {
+            id: script_uid.to_string(),
+            name: Some(script_name.to_string()),
+            description,
+            content_type: content_def.name.to_string(),
+            metadata,
+            tenant_id: None,
+            content: content_map,
+        })
     }
     
-    /// Get script code by UID - returns code with escaped newlines converted to actual newlines
-    async fn get_script_code(&self, code_endpoint: &str, script_uid: &str) -> Result<String> {
+    /// Fetch the current code of one script, for content types pulled with the script code strategy
+    pub async fn fetch_script_code(&self, content_def: &ContentTypeDefinition, script_uid: &str) -> Result<String> {
+        match &content_def.pull_strategy {
+            PullStrategy::ScriptCode { code_endpoint, .. } => self.get_script_code(content_def, code_endpoint, script_uid).await,
+            _ => Err(anyhow::anyhow!("Content type '{}' does not store script code", content_def.name)),
+        }
+    }
+
+    /// Get script code by UID - returns the code byte for byte as the platform stores it
+    async fn get_script_code(&self, content_def: &ContentTypeDefinition, code_endpoint: &str, script_uid: &str) -> Result<String> {
         let code_url = format!("https://{}{}/{}", self.fqdn, self.base_api_path, code_endpoint);
         
         let response = self.client
@@ -1066,22 +1222,79 @@ impl ModuleClient {
             return Err(anyhow::anyhow!("Failed to get script code for UID '{}': HTTP {}", script_uid, response.status()));
         }
         
-        let json: Value = response.json().await.context("Failed to parse script code response")?;
-        
-        let script_code = json.get("reply")
-            .and_then(|r| r.as_str())
-            .ok_or_else(|| anyhow::anyhow!("Script code response missing 'reply' field"))?;
-        
-        // Convert escaped newlines (\n) to actual newlines for readability
-        let code_with_newlines = script_code.replace("\\n", "\n");
-        
-        Ok(code_with_newlines)
+        let json = self.read_json_response(response, content_def.name, Some(script_uid)).await
+            .context("Failed to parse script code response")?;
+        Self::script_code_from_reply(&json)
+    }
+
+    fn script_code_from_reply(json: &Value) -> Result<String> {
+        json.get("reply")
+            .and_then(script_code::decode_reply)
+            .ok_or_else(|| anyhow::anyhow!("Script code response missing 'reply' string"))
     }
     
+    /// Parse a JSON response body, recording the raw bytes when responses are archived
+    /// object_id names the object for per-object requests such as script code.
+    async fn read_json_response(&self, response: Response, content_type: &str, object_id: Option<&str>) -> Result<Value> {
+        let body = response.bytes().await.context("Failed to read response body")?;
+        if let Some(raw) = &self.raw_responses {
+            if let Ok(mut raw) = raw.lock() {
+                raw.push(RawResponse {
+                    content_type: content_type.to_string(),
+                    object_id: object_id.map(str::to_string),
+                    body: body.to_vec(),
+                });
+            }
+        }
+        serde_json::from_slice(&body).context("Failed to parse JSON response")
+    }
+
+    /// Re-derive the objects of a content type from its archived responses
+    /// Script lists are joined with the archived code of each script; a script without
+    /// archived code is skipped, as it would have been on the original pull.
+    pub fn objects_from_archive(&self, content_def: &ContentTypeDefinition, responses: &[&RawResponse]) -> Result<Vec<XsiamObject>> {
+        let parse = |response: &RawResponse| -> Result<Value> {
+            serde_json::from_slice(&response.body)
+                .with_context(|| format!("Failed to parse archived {} response", response.content_type))
+        };
+
+        match &content_def.pull_strategy {
+            PullStrategy::ScriptCode { list_response_path, uid_field, .. } => {
+                let mut codes = HashMap::new();
+                let mut scripts_list = Vec::new();
+                for response in responses {
+                    let json = parse(response)?;
+                    match &response.object_id {
+                        Some(script_uid) => {
+                            codes.insert(script_uid.clone(), Self::script_code_from_reply(&json)?);
+                        }
+                        None => scripts_list.extend(self.scripts_from_list(&json, list_response_path)?),
+                    }
+                }
+
+                let mut script_objects = Vec::new();
+                for script_meta in &scripts_list {
+                    let script_uid = Self::script_uid(script_meta, uid_field)?;
+                    if let Some(script_code) = codes.remove(script_uid) {
+                        script_objects.push(Self::script_object(content_def, script_meta, uid_field, Some(script_code))?);
+                    }
+                }
+                Ok(script_objects)
+            }
+            _ => {
+                let mut objects = Vec::new();
+                for response in responses {
+                    objects.extend(self.extract_items_from_response(&parse(response)?, content_def)?);
+                }
+                Ok(objects)
+            }
+        }
+    }
+
     /// Extract items from JSON response using response_path
     /// Logs warnings when response structure doesn't match expectations to help distinguish
     /// between "no data" vs "API structure changed"
-    fn extract_items_from_response(&self, json: &Value, content_def: &ContentTypeDefinition) -> Result<Vec<XsiamObject>> {
+    pub fn extract_items_from_response(&self, json: &Value, content_def: &ContentTypeDefinition) -> Result<Vec<XsiamObject>> {
         let items = if let Some(path) = content_def.response_path {
             // Try to extract from path - log warning if path doesn't exist
             match self.extract_value_by_path(json, path) {
@@ -1095,7 +1308,7 @@ impl ModuleClient {
                                     .collect();
                                 let mut objects = Vec::new();
                                 for item in &flattened {
-                                    let object = XsiamObject::from_api_response(item, content_def.name)?;
+                                    let object = XsiamObject::from_api_response(item, content_def)?;
                                     objects.push(object);
                                 }
                                 return Ok(objects);
@@ -1125,7 +1338,7 @@ impl ModuleClient {
                         let singleton = vec![json.clone()];
                         let mut objects = Vec::new();
                         for item in &singleton {
-                            let object = XsiamObject::from_api_response(item, content_def.name)?;
+                            let object = XsiamObject::from_api_response(item, content_def)?;
                             objects.push(object);
                         }
                         return Ok(objects);
@@ -1139,7 +1352,7 @@ impl ModuleClient {
         
         let mut objects = Vec::new();
         for item in items {
-            let object = XsiamObject::from_api_response(item, content_def.name)?;
+            let object = XsiamObject::from_api_response(item, content_def)?;
             objects.push(object);
         }
         
@@ -0,0 +1,142 @@
+// SPDX-FileCopyrightText: GoCortexIO
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+// Raw API response archive
+// Each pull can save the response bodies it received, unmodified, to a compressed ZIP
+// outside the instance repository: <archive dir>/<instance>/<module>/<timestamp>.zip, with one
+// entry per response named <content_type>/<sequence>.json, or <content_type>/<sequence>-<object id>.json
+// for responses about a single object such as script code. Objects can be re-derived from it later.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+/// A response body and what it was requested for
+#[derive(Debug, Clone, PartialEq)]
+pub struct RawResponse {
+    pub content_type: String,
+    /// Object the response is about, for per-object requests such as script code
+    pub object_id: Option<String>,
+    pub body: Vec<u8>,
+}
+
+/// Archive directory used when [archive] sets no path
+pub const DEFAULT_DIR: &str = ".gcgit/archive";
+
+/// Directory holding the archives of one module of an instance
+pub fn module_dir(base: &str, instance_name: &str, module_id: &str) -> PathBuf {
+    Path::new(base).join(instance_name).join(module_id)
+}
+
+/// Save the raw responses of a pull, returning the archive path
+/// Names have microsecond precision, and an existing archive is never overwritten.
+pub fn write(dir: &Path, pulled_at: DateTime<Utc>, responses: &[RawResponse]) -> Result<PathBuf> {
+    fs::create_dir_all(dir)
+        .with_context(|| format!("Failed to create archive directory: {}", dir.display()))?;
+    let path = dir.join(format!("{}.zip", pulled_at.format("%Y%m%dT%H%M%S%.6fZ")));
+    let file = fs::OpenOptions::new().write(true).create_new(true).open(&path)
+        .with_context(|| format!("Failed to create archive: {}", path.display()))?;
+
+    let mut zip = ZipWriter::new(file);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    for (sequence, response) in responses.iter().enumerate() {
+        let content_type = &response.content_type;
+        let name = match &response.object_id {
+            Some(object_id) => format!("{content_type}/{sequence:04}-{object_id}.json"),
+            None => format!("{content_type}/{sequence:04}.json"),
+        };
+        zip.start_file(name, options)
+            .with_context(|| format!("Failed to add {content_type} response to archive"))?;
+        zip.write_all(&response.body)
+            .with_context(|| format!("Failed to write {content_type} response to archive"))?;
+    }
+    zip.finish().with_context(|| format!("Failed to finish archive: {}", path.display()))?;
+
+    Ok(path)
+}
+
+/// Read the responses of an archive in the order they were received
+pub fn read(path: &Path) -> Result<Vec<RawResponse>> {
+    let file = fs::File::open(path)
+        .with_context(|| format!("Failed to open archive: {}", path.display()))?;
+    let mut zip = ZipArchive::new(file)
+        .with_context(|| format!("Failed to read archive: {}", path.display()))?;
+
+    let mut responses = Vec::new();
+    for index in 0..zip.len() {
+        let mut entry = zip.by_index(index).context("Failed to access archive entry")?;
+        let name = entry.name().to_string();
+        let Some((content_type, file_name)) = name.split_once('/') else {
+            return Err(anyhow::anyhow!("Unexpected archive entry '{name}' in {}", path.display()));
+        };
+        let object_id = file_name.trim_end_matches(".json")
+            .split_once('-')
+            .map(|(_, object_id)| object_id.to_string());
+        let mut body = Vec::new();
+        entry.read_to_end(&mut body)
+            .with_context(|| format!("Failed to read archive entry '{name}'"))?;
+        responses.push(RawResponse { content_type: content_type.to_string(), object_id, body });
+    }
+    Ok(responses)
+}
+
+/// Most recent archive in a module's archive directory
+/// Archive names are timestamps, so the last name in order is the latest.
+pub fn latest(dir: &Path) -> Result<Option<PathBuf>> {
+    if !dir.exists() {
+        return Ok(None);
+    }
+    let mut archives: Vec<PathBuf> = fs::read_dir(dir)
+        .with_context(|| format!("Failed to read archive directory: {}", dir.display()))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "zip"))
+        .collect();
+    archives.sort();
+    Ok(archives.pop())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_write_read_and_latest() {
+        let base = "test_archive_round_trip";
+        let _ = fs::remove_dir_all(base);
+        let dir = module_dir(base, "prod", "xsiam");
+
+        // Bodies are stored byte for byte, including formatting and key order
+        let response = |content_type: &str, object_id: Option<&str>, body: &[u8]| RawResponse {
+            content_type: content_type.to_string(),
+            object_id: object_id.map(str::to_string),
+            body: body.to_vec(),
+        };
+        let responses = vec![
+            response("biocs", None, b"{\"objects\": [ {\"z\": 1, \"a\": 2} ]}"),
+            response("applications", None, b"{\"data\":[],\"hasNext\":true}"),
+            response("applications", None, b"{\"data\":[],\"hasNext\":false}"),
+            response("scripts", Some("uid-1"), b"{\"reply\": \"return 1\"}"),
+        ];
+        let older = write(&dir, Utc.with_ymd_and_hms(2026, 10, 1, 8, 0, 0).unwrap(), &responses[..1]).unwrap();
+        let newer = write(&dir, Utc.with_ymd_and_hms(2026, 10, 2, 8, 0, 0).unwrap(), &responses).unwrap();
+
+        // Pulls within the same second get their own archives; the same instant is never overwritten
+        let same_second = Utc.with_ymd_and_hms(2026, 10, 2, 8, 0, 0).unwrap() + chrono::Duration::milliseconds(250);
+        let newest = write(&dir, same_second, &responses[..1]).unwrap();
+        assert!(write(&dir, same_second, &responses[..1]).is_err());
+
+        let latest_path = latest(&dir).unwrap();
+        let read_back = read(&newer).unwrap();
+        let older_name = older.file_name().unwrap().to_string_lossy().into_owned();
+        let _ = fs::remove_dir_all(base);
+
+        assert_eq!(older_name, "20261001T080000.000000Z.zip");
+        assert_eq!(latest_path, Some(newest));
+        assert_eq!(read_back, responses);
+    }
+}
@@ -0,0 +1,131 @@
+// SPDX-FileCopyrightText: GoCortexIO
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+// Management audit log entries attached to pull commits
+// Records who changed what on the platform between two pulls
+
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+
+/// Notes reference holding audit log entries for pull commits
+pub const AUDIT_NOTES_REF: &str = "refs/notes/gcgit-audit";
+
+/// A single management audit log entry
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuditEntry {
+    pub timestamp: Option<DateTime<Utc>>,
+    pub user: String,
+    pub action: String,
+    pub object: String,
+    pub result: String,
+}
+
+impl AuditEntry {
+    /// Build an entry from a management_logs API record
+    pub fn from_api_record(record: &Value) -> Self {
+        let text = |fields: &[&str]| {
+            fields.iter()
+                .find_map(|field| record.get(*field).and_then(|v| v.as_str()).filter(|s| !s.is_empty()))
+                .unwrap_or_default()
+                .to_string()
+        };
+
+        let timestamp = record.get("AUDIT_INSERT_TIME")
+            .and_then(|v| v.as_i64())
+            .and_then(DateTime::from_timestamp_millis);
+
+        let entity = text(&["AUDIT_ENTITY"]);
+        let description = text(&["AUDIT_DESCRIPTION"]);
+        let object = match (entity.is_empty(), description.is_empty()) {
+            (false, false) => format!("{entity}: {description}"),
+            (false, true) => entity,
+            _ => description,
+        };
+
+        Self {
+            timestamp,
+            user: text(&["AUDIT_OWNER_EMAIL", "AUDIT_OWNER_NAME"]),
+            action: text(&["AUDIT_ENTITY_SUBTYPE"]),
+            object,
+            result: text(&["AUDIT_RESULT"]),
+        }
+    }
+
+    /// Whether the entry is about one of the given objects, matched by name or ID
+    /// An identifier only matches as a whole token, so ID 12 does not match an entry about 123.
+    pub fn mentions_any(&self, identifiers: &[String]) -> bool {
+        identifiers.iter().any(|identifier| !identifier.is_empty() && mentions(&self.object, identifier))
+    }
+}
+
+/// Whether text contains the identifier bounded by non-alphanumeric characters or the ends
+fn mentions(text: &str, identifier: &str) -> bool {
+    let is_word = |c: Option<char>| c.is_some_and(char::is_alphanumeric);
+    text.match_indices(identifier).any(|(start, _)| {
+        let before = text[..start].chars().next_back();
+        let after = text[start + identifier.len()..].chars().next();
+        !is_word(before) && !is_word(after)
+    })
+}
+
+/// Render audit entries as the body of a Git note
+pub fn format_note(since: DateTime<Utc>, until: DateTime<Utc>, entries: &[AuditEntry]) -> String {
+    let mut note = format!(
+        "Management audit log {} .. {} ({} entries)\n\n",
+        since.to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+        until.to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+        entries.len()
+    );
+
+    for entry in entries {
+        let timestamp = entry.timestamp
+            .map(|t| t.to_rfc3339_opts(chrono::SecondsFormat::Secs, true))
+            .unwrap_or_else(|| "unknown time".to_string());
+        note.push_str(&format!("{timestamp}  {}  {}  {}", entry.user, entry.action, entry.object));
+        if !entry.result.is_empty() {
+            note.push_str(&format!("  [{}]", entry.result));
+        }
+        note.push('\n');
+    }
+
+    note
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_entry_from_api_record() {
+        let record = json!({
+            "AUDIT_INSERT_TIME": 1760000000000i64,
+            "AUDIT_OWNER_NAME": "Jane Doe",
+            "AUDIT_OWNER_EMAIL": "jane@example.com",
+            "AUDIT_ENTITY": "CORRELATION_RULES",
+            "AUDIT_ENTITY_SUBTYPE": "Edit",
+            "AUDIT_DESCRIPTION": "Suspicious Login",
+            "AUDIT_RESULT": "SUCCESS"
+        });
+
+        let entry = AuditEntry::from_api_record(&record);
+        assert_eq!(entry.user, "jane@example.com");
+        assert_eq!(entry.action, "Edit");
+        assert_eq!(entry.object, "CORRELATION_RULES: Suspicious Login");
+        assert_eq!(entry.timestamp, DateTime::from_timestamp(1760000000, 0));
+        assert!(entry.mentions_any(&["Other Rule".to_string(), "Suspicious Login".to_string()]));
+        assert!(!entry.mentions_any(&["Other Rule".to_string(), String::new()]));
+
+        // Identifiers match whole tokens only
+        let by_id = AuditEntry { object: "CORRELATION_RULES: rule 123 (Login1200)".to_string(), ..entry.clone() };
+        assert!(by_id.mentions_any(&["123".to_string()]));
+        assert!(by_id.mentions_any(&["Login1200".to_string()]));
+        assert!(!by_id.mentions_any(&["12".to_string(), "1200".to_string(), "Login".to_string()]));
+
+        let since = DateTime::from_timestamp(1759990000, 0).unwrap();
+        let until = DateTime::from_timestamp(1760010000, 0).unwrap();
+        let note = format_note(since, until, &[entry]);
+        assert!(note.starts_with("Management audit log 2025-10-09T06:06:40Z .. 2025-10-09T11:40:00Z (1 entries)\n\n"));
+        assert!(note.contains("2025-10-09T08:53:20Z  jane@example.com  Edit  CORRELATION_RULES: Suspicious Login  [SUCCESS]\n"));
+    }
+}
@@ -0,0 +1,335 @@
+// SPDX-FileCopyrightText: GoCortexIO
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+// Golden baseline compliance checking
+// A baseline directory uses the instance layout (module/content_type/object.yaml) and lists
+// objects every instance must have. An optional baseline.toml sets severity and match mode by path.
+
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use serde::Deserialize;
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+
+use crate::compare;
+use crate::modules::{ContentTypeDefinition, Module};
+use crate::object_diff::{self, FieldChange};
+use crate::parser::YamlParser;
+use crate::types::XsiamObject;
+
+/// Rules file at the root of a baseline directory
+pub const RULES_FILE: &str = "baseline.toml";
+
+/// How serious a deviation from the baseline is
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Low,
+    #[default]
+    Medium,
+    High,
+    Critical,
+}
+
+impl Severity {
+    pub fn label(self) -> &'static str {
+        match self {
+            Severity::Low => "LOW",
+            Severity::Medium => "MEDIUM",
+            Severity::High => "HIGH",
+            Severity::Critical => "CRITICAL",
+        }
+    }
+}
+
+/// How an instance object is compared with its baseline object
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MatchMode {
+    /// Every field in the baseline object must have the same value; other fields are ignored
+    #[default]
+    Subset,
+    /// The objects must be logically equal
+    Exact,
+}
+
+#[derive(Debug, Default, Clone, Deserialize)]
+struct RuleSettings {
+    #[serde(default)]
+    severity: Option<Severity>,
+    #[serde(default)]
+    mode: Option<MatchMode>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PathRule {
+    /// Path prefix relative to the baseline root, e.g. xsiam/authentication_settings
+    path: String,
+    #[serde(flatten)]
+    settings: RuleSettings,
+}
+
+/// Severity and match mode rules from baseline.toml
+#[derive(Debug, Default, Deserialize)]
+pub struct BaselineRules {
+    #[serde(default)]
+    defaults: RuleSettings,
+    #[serde(default)]
+    rules: Vec<PathRule>,
+}
+
+impl BaselineRules {
+    /// Load rules from a baseline directory, using defaults if there is no rules file
+    pub fn load(baseline_dir: &str) -> Result<Self> {
+        let rules_path = Path::new(baseline_dir).join(RULES_FILE);
+        if !rules_path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(&rules_path)
+            .with_context(|| format!("Failed to read baseline rules: {}", rules_path.display()))?;
+        toml::from_str(&content)
+            .with_context(|| format!("Failed to parse baseline rules: {}", rules_path.display()))
+    }
+
+    /// Severity and match mode for a baseline object; the longest matching path prefix wins
+    /// Prefixes match whole path segments, so xsiam/bioc does not cover xsiam/biocs.
+    pub fn resolve(&self, relative_path: &str) -> (Severity, MatchMode) {
+        let mut matching: Vec<&PathRule> = self.rules.iter()
+            .filter(|rule| {
+                let prefix = rule.path.trim_end_matches('/');
+                prefix.is_empty()
+                    || relative_path == prefix
+                    || relative_path.strip_prefix(prefix).is_some_and(|rest| rest.starts_with('/'))
+            })
+            .collect();
+        matching.sort_by_key(|rule| rule.path.len());
+
+        let mut severity = self.defaults.severity.unwrap_or_default();
+        let mut mode = self.defaults.mode.unwrap_or_default();
+        for rule in matching {
+            severity = rule.settings.severity.unwrap_or(severity);
+            mode = rule.settings.mode.unwrap_or(mode);
+        }
+        (severity, mode)
+    }
+}
+
+/// What is wrong with an instance compared with one baseline object
+#[derive(Debug)]
+pub enum DeviationKind {
+    /// No matching object exists on the instance
+    Missing,
+    /// Fields that differ; `old` is the baseline value and `new` the instance value
+    Differs(Vec<FieldChange>),
+}
+
+/// A baseline object the instance does not satisfy
+#[derive(Debug)]
+pub struct Deviation {
+    /// Baseline file path relative to the baseline root
+    pub path: String,
+    pub name: String,
+    pub severity: Severity,
+    pub kind: DeviationKind,
+}
+
+/// Check an instance against every baseline object for the given modules
+/// Deviations are returned most severe first.
+pub fn check(yaml_parser: &YamlParser, baseline_dir: &str, instance_name: &str, modules: &[&dyn Module]) -> Result<Vec<Deviation>> {
+    let rules = BaselineRules::load(baseline_dir)?;
+    let mut deviations = Vec::new();
+
+    for module in modules {
+        let baseline_module_dir = format!("{baseline_dir}/{}", module.id());
+        let mut instance_objects = compare::load_module_objects(yaml_parser, instance_name, *module)?;
+
+        for content_def in module.content_types() {
+            let actual = instance_objects.remove(content_def.name).unwrap_or_default();
+            for file in yaml_parser.get_local_files(&baseline_module_dir, &[content_def.name])? {
+                let required = yaml_parser.parse_file(&file)?;
+                let relative_path = file.strip_prefix(&format!("{baseline_dir}/")).unwrap_or(&file).to_string();
+                let (severity, mode) = rules.resolve(&relative_path);
+
+                if let Some(kind) = check_object(yaml_parser, &content_def, &required, find_match(&required, &actual), mode)? {
+                    deviations.push(Deviation {
+                        path: relative_path,
+                        name: compare::display_name(&required),
+                        severity,
+                        kind,
+                    });
+                }
+            }
+        }
+    }
+
+    deviations.sort_by(|a, b| b.severity.cmp(&a.severity).then_with(|| a.path.cmp(&b.path)));
+    Ok(deviations)
+}
+
+/// Find the instance object for a baseline object, by name when it has one since IDs are tenant-specific
+fn find_match<'a>(required: &XsiamObject, actual: &'a [XsiamObject]) -> Option<&'a XsiamObject> {
+    match required.name.as_deref().filter(|name| !name.trim().is_empty()) {
+        Some(name) => actual.iter().find(|object| object.name.as_deref() == Some(name)),
+        None => actual.iter().find(|object| object.id == required.id),
+    }
+}
+
+/// Compare one instance object with its baseline object
+pub fn check_object(yaml_parser: &YamlParser, content_def: &ContentTypeDefinition, required: &XsiamObject, actual: Option<&XsiamObject>, mode: MatchMode) -> Result<Option<DeviationKind>> {
+    let Some(actual) = actual else {
+        return Ok(Some(DeviationKind::Missing));
+    };
+
+    let required = without_tenant_ids(content_def, required);
+    let mut actual = without_tenant_ids(content_def, actual);
+    actual.id = required.id.clone();
+
+    let fields = match mode {
+        MatchMode::Exact => {
+            if yaml_parser.objects_are_logically_equal(&required, &actual)? {
+                Vec::new()
+            } else {
+                object_diff::field_changes(&required, &actual)
+            }
+        }
+        MatchMode::Subset => subset_mismatches(&required, &actual),
+    };
+
+    Ok((!fields.is_empty()).then_some(DeviationKind::Differs(fields)))
+}
+
+/// Drop fields holding tenant-assigned IDs, which never match between tenants
+fn without_tenant_ids(content_def: &ContentTypeDefinition, object: &XsiamObject) -> XsiamObject {
+    let mut cleaned = object.clone();
+    for field in content_def.tenant_specific_id_fields() {
+        cleaned.content.remove(field);
+    }
+    cleaned
+}
+
+/// Fields set in the baseline object whose instance value differs
+/// An empty baseline description places no requirement on the instance.
+fn subset_mismatches(required: &XsiamObject, actual: &XsiamObject) -> Vec<FieldChange> {
+    let mut mismatches = Vec::new();
+
+    if let Some(name) = &required.name {
+        if actual.name.as_ref() != Some(name) {
+            mismatches.push(FieldChange {
+                path: "name".to_string(),
+                old: Some(Value::String(name.clone())),
+                new: actual.name.clone().map(Value::String),
+            });
+        }
+    }
+    if !required.description.is_empty() && required.description != actual.description {
+        mismatches.push(FieldChange {
+            path: "description".to_string(),
+            old: Some(Value::String(required.description.clone())),
+            new: Some(Value::String(actual.description.clone())),
+        });
+    }
+
+    let mut keys: Vec<&String> = required.content.keys().collect();
+    keys.sort();
+    for key in keys {
+        subset_value(key, &required.content[key], actual.content.get(key), &mut mismatches);
+    }
+
+    mismatches
+}
+
+fn subset_value(path: &str, required: &Value, actual: Option<&Value>, mismatches: &mut Vec<FieldChange>) {
+    if let (Value::Object(required_map), Some(Value::Object(actual_map))) = (required, actual) {
+        let mut keys: Vec<&String> = required_map.keys().collect();
+        keys.sort();
+        for key in keys {
+            subset_value(&format!("{path}.{key}"), &required_map[key], actual_map.get(key), mismatches);
+        }
+        return;
+    }
+
+    if actual != Some(required) {
+        mismatches.push(FieldChange {
+            path: path.to_string(),
+            old: Some(required.clone()),
+            new: actual.cloned(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn correlation_searches() -> ContentTypeDefinition {
+        crate::modules::content_type_definition("xsiam", "correlation_searches")
+    }
+
+    fn rule(id: &str, enabled: bool, severity: &str) -> XsiamObject {
+        let mut object = XsiamObject::new(id.to_string(), "Mandatory".to_string(), "correlation_searches".to_string());
+        object.content.insert("rule_id".to_string(), json!(id));
+        object.content.insert("is_enabled".to_string(), json!(enabled));
+        object.content.insert("alert".to_string(), json!({"severity": severity, "category": "Other"}));
+        object
+    }
+
+    #[test]
+    fn test_subset_ignores_extra_fields_and_tenant_ids() {
+        let parser = YamlParser::new();
+        let mut required = XsiamObject::new("1".to_string(), "Mandatory".to_string(), "correlation_searches".to_string());
+        required.content.insert("rule_id".to_string(), json!("1"));
+        required.content.insert("is_enabled".to_string(), json!(true));
+        required.content.insert("alert".to_string(), json!({"severity": "high"}));
+
+        let compliant = rule("77", true, "high");
+        assert!(check_object(&parser, &correlation_searches(), &required, Some(&compliant), MatchMode::Subset).unwrap().is_none());
+
+        let deviating = rule("77", false, "low");
+        let Some(DeviationKind::Differs(fields)) = check_object(&parser, &correlation_searches(), &required, Some(&deviating), MatchMode::Subset).unwrap() else {
+            panic!("expected field differences");
+        };
+        let paths: Vec<&str> = fields.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, vec!["alert.severity", "is_enabled"]);
+        assert_eq!(fields[1].old, Some(json!(true)));
+
+        assert!(matches!(check_object(&parser, &correlation_searches(), &required, None, MatchMode::Subset).unwrap(), Some(DeviationKind::Missing)));
+    }
+
+    #[test]
+    fn test_exact_flags_extra_fields() {
+        let parser = YamlParser::new();
+        let required = rule("1", true, "high");
+        let mut actual = rule("77", true, "high");
+        assert!(check_object(&parser, &correlation_searches(), &required, Some(&actual), MatchMode::Exact).unwrap().is_none());
+
+        actual.content.insert("mitre".to_string(), json!(["T1078"]));
+        assert!(check_object(&parser, &correlation_searches(), &required, Some(&actual), MatchMode::Exact).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_rules_longest_prefix_wins() {
+        let rules: BaselineRules = toml::from_str(r#"
+            [defaults]
+            severity = "low"
+
+            [[rules]]
+            path = "xsiam/"
+            severity = "high"
+
+            [[rules]]
+            path = "xsiam/authentication_settings"
+            severity = "critical"
+            mode = "exact"
+
+            [[rules]]
+            path = "xsiam/bioc"
+            severity = "critical"
+        "#).unwrap();
+
+        assert_eq!(rules.resolve("xsiam/authentication_settings/SSO.yaml"), (Severity::Critical, MatchMode::Exact));
+        assert_eq!(rules.resolve("xsiam/biocs/Rule.yaml"), (Severity::High, MatchMode::Subset));
+        assert_eq!(rules.resolve("appsec/rules/Rule.yaml"), (Severity::Low, MatchMode::Subset));
+    }
+}
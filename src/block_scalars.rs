@@ -0,0 +1,173 @@
+// SPDX-FileCopyrightText: GoCortexIO
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+// YAML output with literal block scalars for multi-line strings
+// The YAML emitter falls back to escaped double-quoted strings for text containing tabs or
+// trailing spaces, which is common in code and XQL. Such strings are written as `|` blocks
+// instead, provided the block reads back to exactly the same text.
+
+use anyhow::{Context, Result};
+use serde_yaml_ng::{Mapping, Value};
+
+/// Prefix of the placeholders standing in for block scalars while the document is emitted
+const PLACEHOLDER_PREFIX: &str = "__gcgit_block_scalar_";
+
+/// Serialise a YAML value, writing multi-line strings as literal block scalars
+pub fn to_string(value: &Value) -> Result<String> {
+    let plain = serde_yaml_ng::to_string(value).context("Failed to convert to YAML string")?;
+
+    let mut blocks = Vec::new();
+    let placeheld = replace_multiline(value, &mut blocks);
+    if blocks.is_empty() {
+        return Ok(plain);
+    }
+
+    let emitted = serde_yaml_ng::to_string(&placeheld).context("Failed to convert to YAML string")?;
+    let Some(output) = substitute_blocks(&emitted, &blocks) else {
+        return Ok(plain);
+    };
+
+    // Only use the block form when the whole document reads back unchanged
+    match serde_yaml_ng::from_str::<Value>(&output) {
+        Ok(reread) if reread == *value => Ok(output),
+        _ => Ok(plain),
+    }
+}
+
+/// Copy of the value with each multi-line string the emitter would escape replaced by a placeholder
+fn replace_multiline(value: &Value, blocks: &mut Vec<String>) -> Value {
+    match value {
+        Value::String(text) if needs_block(text) => {
+            blocks.push(text.clone());
+            Value::String(format!("{PLACEHOLDER_PREFIX}{}__", blocks.len() - 1))
+        }
+        Value::Sequence(items) => Value::Sequence(items.iter().map(|item| replace_multiline(item, blocks)).collect()),
+        Value::Mapping(map) => {
+            let mut replaced = Mapping::new();
+            for (key, item) in map {
+                replaced.insert(key.clone(), replace_multiline(item, blocks));
+            }
+            Value::Mapping(replaced)
+        }
+        _ => value.clone(),
+    }
+}
+
+/// Whether a string is multi-line, not already emitted as a block, and survives as one
+fn needs_block(text: &str) -> bool {
+    if !text.contains('\n') || text.trim().is_empty() {
+        return false;
+    }
+    let emitted = serde_yaml_ng::to_string(&Value::String(text.to_string())).unwrap_or_default();
+    if emitted.starts_with('|') {
+        return false;
+    }
+    let document = format!("value: {}", render_block(text, 2));
+    matches!(
+        serde_yaml_ng::from_str::<Mapping>(&document).ok().and_then(|map| map.get("value").cloned()),
+        Some(Value::String(reread)) if reread == text
+    )
+}
+
+/// Literal block scalar for the text: the header followed by lines indented by `indent` spaces
+fn render_block(text: &str, indent: usize) -> String {
+    let body = text.trim_end_matches('\n');
+    let chomping = match text.len() - body.len() {
+        0 => "-",
+        1 => "",
+        _ => "+",
+    };
+    // A first line starting with a space would otherwise be taken as extra indentation
+    let first_line = body.lines().find(|line| !line.is_empty()).unwrap_or_default();
+    let indicator = if first_line.starts_with(' ') { indent.to_string() } else { String::new() };
+
+    let mut block = format!("|{indicator}{chomping}\n");
+    for line in text.strip_suffix('\n').unwrap_or(text).split('\n') {
+        if !line.is_empty() {
+            block.push_str(&" ".repeat(indent));
+            block.push_str(line);
+        }
+        block.push('\n');
+    }
+    block
+}
+
+/// Replace each placeholder line ending with the block it stands for
+/// Returns None if a placeholder is not found where a block scalar can be written.
+fn substitute_blocks(emitted: &str, blocks: &[String]) -> Option<String> {
+    let mut output = String::with_capacity(emitted.len());
+    let mut substituted = 0;
+
+    for line in emitted.lines() {
+        let Some((prefix, index)) = placeholder(line) else {
+            output.push_str(line);
+            output.push('\n');
+            continue;
+        };
+
+        // Content is indented past the mapping key or sequence dash that owns it
+        let leading = line.len() - line.trim_start_matches(' ').len();
+        let mut rest = &prefix[leading..];
+        let mut column = leading;
+        while let Some(stripped) = rest.strip_prefix("- ") {
+            rest = stripped;
+            column += 2;
+        }
+        let indent = if rest.is_empty() { column } else { column + 2 };
+
+        output.push_str(prefix);
+        output.push_str(&render_block(blocks.get(index)?, indent));
+        substituted += 1;
+    }
+
+    (substituted == blocks.len()).then_some(output)
+}
+
+/// Split a line ending in a placeholder into the text before it and the block index
+fn placeholder(line: &str) -> Option<(&str, usize)> {
+    let start = line.find(PLACEHOLDER_PREFIX)?;
+    let index = line[start + PLACEHOLDER_PREFIX.len()..].strip_suffix("__")?.parse().ok()?;
+    let prefix = &line[..start];
+    (prefix.ends_with(": ") || prefix.ends_with("- ")).then_some((prefix, index))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(value: &Value) -> String {
+        let output = to_string(value).unwrap();
+        assert_eq!(serde_yaml_ng::from_str::<Value>(&output).unwrap(), *value);
+        output
+    }
+
+    #[test]
+    fn test_code_with_tabs_and_trailing_spaces_uses_block() {
+        let mut map = Mapping::new();
+        map.insert("code".into(), "def main():\n\treturn 1 \n".into());
+        map.insert("name".into(), "Cleanup".into());
+
+        let output = round_trip(&Value::Mapping(map));
+        assert_eq!(output, "code: |\n  def main():\n  \treturn 1 \nname: Cleanup\n");
+    }
+
+    #[test]
+    fn test_nested_blocks_and_chomping() {
+        let yaml = "rules:\n- query: \"a \\nb\"\n  tags:\n  - \"x\\t\\ny\\n\\n\"\n";
+        let value: Value = serde_yaml_ng::from_str(yaml).unwrap();
+
+        let output = round_trip(&value);
+        assert_eq!(output, "rules:\n- query: |-\n    a \n    b\n  tags:\n  - |+\n    x\t\n    y\n\n");
+    }
+
+    #[test]
+    fn test_unrepresentable_text_stays_quoted() {
+        let mut map = Mapping::new();
+        map.insert("crlf".into(), "a\r\nb\r\n".into());
+        map.insert("indented".into(), "  lead\t\nx\n".into());
+
+        let output = round_trip(&Value::Mapping(map));
+        assert!(output.contains("crlf: \"a\\r\\nb\\r\\n\""));
+        assert!(output.contains("indented: |2\n    lead\t\n  x\n"));
+    }
+}
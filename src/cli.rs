@@ -1,7 +1,9 @@
 // SPDX-FileCopyrightText: GoCortexIO
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
+
+use crate::baseline::Severity;
 
 #[derive(Parser)]
 #[command(name = "gcgit")]
@@ -36,6 +38,9 @@ pub enum Commands {
         /// Instance name to check (optional - shows all if not specified)
         #[arg(long)]
         instance: Option<String>,
+        /// Run for every instance in a group from .gcgit/global_config.toml
+        #[arg(long, conflicts_with = "instance")]
+        group: Option<String>,
     },
     /// Streamlined deployment: validate + add + commit + push to platform
     Deploy {
@@ -56,6 +61,145 @@ pub enum Commands {
         /// Specific files to validate (if not specified, validates all YAML files in instance)
         files: Vec<String>,
     },
+    /// Restore objects or a whole instance to a past revision
+    Restore {
+        /// Instance name
+        #[arg(long)]
+        instance: Option<String>,
+        /// Commit, tag or other Git revision to restore from
+        #[arg(long)]
+        rev: String,
+        /// Write the restored objects back to the platform
+        #[arg(long)]
+        push: bool,
+        /// Paths to restore, relative to the instance (restores the whole instance if not specified)
+        paths: Vec<String>,
+    },
+    /// Tag the current state of an instance repository
+    Snapshot {
+        /// Instance name
+        #[arg(long)]
+        instance: Option<String>,
+        /// Tag name, e.g. 2026-Q4-review
+        #[arg(long)]
+        tag: String,
+        /// Tag message (defaults to a generated description)
+        #[arg(short, long)]
+        message: Option<String>,
+    },
+    /// Render a grouped change report between two revisions
+    Report {
+        /// Instance name
+        #[arg(long)]
+        instance: Option<String>,
+        /// Starting tag or revision
+        #[arg(long)]
+        from: String,
+        /// Ending tag or revision
+        #[arg(long, default_value = "HEAD")]
+        to: String,
+        /// Output format
+        #[arg(long, value_enum, default_value_t = ReportFormat::Md)]
+        format: ReportFormat,
+        /// Write the report to a file instead of standard output
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+    /// Compare two instances side by side
+    Compare {
+        /// Left-hand instance, e.g. dev
+        #[arg(long)]
+        left: String,
+        /// Right-hand instance, e.g. prod
+        #[arg(long)]
+        right: String,
+        /// Limit the comparison to one module
+        #[arg(long)]
+        module: Option<String>,
+    },
+    /// Copy objects from one instance to another and stage them on the target
+    Promote {
+        /// Source instance, e.g. dev
+        #[arg(long)]
+        from: String,
+        /// Target instance, e.g. prod
+        #[arg(long)]
+        to: String,
+        /// Content type of the objects to promote
+        #[arg(long)]
+        content_type: String,
+        /// IDs or names of the objects to promote (comma-separated or repeated)
+        #[arg(long, value_delimiter = ',', required = true)]
+        select: Vec<String>,
+    },
+    /// Verify a commit's signature against the configured allowed signers
+    Verify {
+        /// Instance name
+        #[arg(long)]
+        instance: Option<String>,
+        /// Commit to verify
+        #[arg(long, default_value = "HEAD")]
+        rev: String,
+    },
+    /// Push the instance repository to its configured Git remote
+    Sync {
+        /// Instance name
+        #[arg(long)]
+        instance: Option<String>,
+    },
+    /// Work with platform/<module> branches that mirror platform state
+    Platform {
+        #[command(subcommand)]
+        command: PlatformCommands,
+    },
+    /// Check instances against a golden baseline
+    Baseline {
+        #[command(subcommand)]
+        command: BaselineCommands,
+    },
+}
+
+// Commands for golden baseline compliance
+#[derive(Subcommand)]
+pub enum BaselineCommands {
+    /// Report where an instance deviates from the baseline
+    Check {
+        /// Instance name
+        #[arg(long)]
+        instance: Option<String>,
+        /// Baseline directory (defaults to [baseline] path in config.toml)
+        #[arg(long)]
+        baseline: Option<String>,
+        /// Limit the check to one module
+        #[arg(long)]
+        module: Option<String>,
+        /// Lowest severity that makes the command fail
+        #[arg(long, value_enum, default_value_t = Severity::Low)]
+        fail_on: Severity,
+    },
+}
+
+// Commands for the platform-tracking branch model
+#[derive(Subcommand)]
+pub enum PlatformCommands {
+    /// Merge pulled platform changes into the checked-out branch
+    Merge {
+        /// Instance name
+        #[arg(long)]
+        instance: Option<String>,
+        /// Module to merge (merges every platform branch if not specified)
+        #[arg(long)]
+        module: Option<String>,
+    },
+    /// Show what the checked-out branch has that the platform does not
+    Diff {
+        /// Instance name
+        #[arg(long)]
+        instance: Option<String>,
+        /// Module to compare (compares every platform branch if not specified)
+        #[arg(long)]
+        module: Option<String>,
+    },
 }
 
 // Generic module commands that work across all modules
@@ -72,18 +216,60 @@ pub enum ModuleCommands {
         /// Instance name
         #[arg(long)]
         instance: Option<String>,
+        /// Run for every instance in a group from .gcgit/global_config.toml
+        #[arg(long, conflicts_with = "instance")]
+        group: Option<String>,
+        /// How to handle uncommitted local edits to files the pull would overwrite
+        #[arg(long, value_enum, default_value_t = LocalChangesMode::Abort)]
+        local_changes: LocalChangesMode,
+        /// Push the instance repository to its Git remote after pulling
+        #[arg(long)]
+        git_push: bool,
+        /// Create one commit per platform user, authored by the user who last modified the objects
+        #[arg(long)]
+        attribute_authors: bool,
+        /// Attach management audit log entries since the previous pull as a Git note
+        #[arg(long)]
+        audit_log: bool,
     },
     /// Show differences between local and remote
     Diff {
         /// Instance name
         #[arg(long)]
         instance: Option<String>,
+        /// Run for every instance in a group from .gcgit/global_config.toml
+        #[arg(long, conflicts_with = "instance")]
+        group: Option<String>,
     },
     /// Test API connectivity
     Test {
         /// Instance name
         #[arg(long)]
         instance: Option<String>,
+        /// Run for every instance in a group from .gcgit/global_config.toml
+        #[arg(long, conflicts_with = "instance")]
+        group: Option<String>,
+    },
+    /// Check that local script code reproduces the platform code byte for byte
+    VerifyCode {
+        /// Instance name
+        #[arg(long)]
+        instance: Option<String>,
+        /// Also fetch each script's current code from the platform and compare
+        #[arg(long)]
+        remote: bool,
+    },
+    /// Re-derive object YAML from an archived raw API response
+    Rederive {
+        /// Instance name
+        #[arg(long)]
+        instance: Option<String>,
+        /// Archive file to read; defaults to the latest archive for the module
+        #[arg(long)]
+        archive: Option<String>,
+        /// Only re-derive this content type
+        #[arg(long)]
+        content_type: Option<String>,
     },
     /// Delete an object from the platform
     Delete {
@@ -98,3 +284,23 @@ pub enum ModuleCommands {
         id: String,
     },
 }
+
+/// Handling of uncommitted local edits when pulling
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum LocalChangesMode {
+    /// Refuse to pull while local edits exist
+    Abort,
+    /// Stash local edits, pull, then re-apply them
+    Stash,
+    /// Commit platform state, then merge local edits back field by field
+    Merge,
+}
+
+/// Output format for change reports
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum ReportFormat {
+    /// Markdown
+    Md,
+    /// Standalone HTML page
+    Html,
+}
@@ -0,0 +1,187 @@
+// SPDX-FileCopyrightText: GoCortexIO
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+// Pull commit messages
+// A configurable subject line followed by machine-readable trailers describing the pull
+
+use git2::Delta;
+use std::collections::BTreeMap;
+
+use crate::git_wrapper::{CommitAuthor, FileChange};
+use crate::sidecar;
+
+pub const TRAILER_MODULE: &str = "Gcgit-Module";
+pub const TRAILER_INSTANCE: &str = "Gcgit-Instance";
+pub const TRAILER_TENANT: &str = "Gcgit-Tenant";
+pub const TRAILER_CHANGED: &str = "Gcgit-Changed";
+
+/// Details of a pull used to build its commit message
+pub struct PullCommitInfo<'a> {
+    pub module_id: &'a str,
+    pub instance_name: &'a str,
+    /// Platform FQDN the module was pulled from
+    pub tenant: &'a str,
+    /// Changed files relative to the instance, e.g. xsiam/biocs/Rule.yaml
+    pub changes: &'a [FileChange],
+}
+
+/// Build the commit message for a pull
+/// The template may use {module}, {MODULE}, {instance}, {tenant}, {count} and {summary}.
+/// Without a template the subject matches the historical "Auto-commit: Updated ..." form.
+pub fn pull_commit_message(template: Option<&str>, info: &PullCommitInfo) -> String {
+    // Sidecar files such as script code are reported as changes to their object
+    let changes = sidecar::object_changes(info.changes);
+    let names: Vec<String> = changes.iter()
+        .map(|(path, _)| {
+            // Extract just the filename from the path for readability
+            let filename = path.split('/').next_back().unwrap_or(path);
+            filename.replace(".yaml", "")
+        })
+        .collect();
+
+    let module_upper = info.module_id.to_uppercase();
+    let summary = if names.len() <= 3 {
+        names.join(", ")
+    } else {
+        format!("{} files ({})", names.len(), names[..2].join(", "))
+    };
+
+    let subject = match template {
+        None if names.len() > 3 => format!(
+            "Auto-commit: Updated {} files from {module_upper} ({})",
+            names.len(),
+            names[..2].join(", ")
+        ),
+        Some(template) => template
+            .replace("{module}", info.module_id)
+            .replace("{MODULE}", &module_upper)
+            .replace("{instance}", info.instance_name)
+            .replace("{tenant}", info.tenant)
+            .replace("{count}", &names.len().to_string())
+            .replace("{summary}", &summary),
+        None => format!("Auto-commit: Updated {summary} from {module_upper}"),
+    };
+
+    let mut message = subject.trim_end().to_string();
+    message.push_str("\n\n");
+    message.push_str(&format!("{TRAILER_MODULE}: {}\n", info.module_id));
+    message.push_str(&format!("{TRAILER_INSTANCE}: {}\n", info.instance_name));
+    message.push_str(&format!("{TRAILER_TENANT}: {}\n", info.tenant));
+    for (content_type, counts) in change_counts(&changes) {
+        message.push_str(&format!(
+            "{TRAILER_CHANGED}: {content_type} added={} modified={} removed={}\n",
+            counts[0], counts[1], counts[2]
+        ));
+    }
+
+    message
+}
+
+/// Read the trailers of a commit message as (key, value) pairs
+pub fn parse_trailers(message: &str) -> Vec<(String, String)> {
+    let last_paragraph = message.trim_end().rsplit("\n\n").next().unwrap_or_default();
+    last_paragraph.lines()
+        .filter_map(|line| line.split_once(": "))
+        .filter(|(key, _)| !key.is_empty() && !key.contains(' '))
+        .map(|(key, value)| (key.to_string(), value.trim().to_string()))
+        .collect()
+}
+
+/// Check whether a commit message belongs to a pull of the given module and instance
+pub fn is_pull_commit(message: &str, module_id: &str, instance_name: &str) -> bool {
+    let trailers = parse_trailers(message);
+    let has = |key: &str, value: &str| trailers.iter().any(|(k, v)| k == key && v == value);
+    has(TRAILER_MODULE, module_id) && has(TRAILER_INSTANCE, instance_name)
+}
+
+/// Git author identity for a platform user
+/// Users recorded by email keep their address; other names get an address on the tenant's domain.
+pub fn author_for_user(user: &str, tenant: &str) -> CommitAuthor {
+    let email = if user.contains('@') {
+        user.to_string()
+    } else {
+        format!("{}@{tenant}", user.replace(' ', "."))
+    };
+    CommitAuthor { name: user.to_string(), email }
+}
+
+/// Count added, modified and removed files per content type directory
+fn change_counts(changes: &[FileChange]) -> BTreeMap<String, [usize; 3]> {
+    let mut counts: BTreeMap<String, [usize; 3]> = BTreeMap::new();
+    for (path, delta) in changes {
+        // module/content_type/file.yaml
+        let content_type = path.split('/').nth(1).unwrap_or("unknown").to_string();
+        let slot = match delta {
+            Delta::Added => 0,
+            Delta::Deleted => 2,
+            _ => 1,
+        };
+        counts.entry(content_type).or_default()[slot] += 1;
+    }
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn changes() -> Vec<FileChange> {
+        vec![
+            ("xsiam/biocs/Rule_A.yaml".to_string(), Delta::Added),
+            ("xsiam/biocs/Rule_B.yaml".to_string(), Delta::Modified),
+            ("xsiam/scripts/Cleanup.yaml".to_string(), Delta::Modified),
+        ]
+    }
+
+    #[test]
+    fn test_default_subject_and_trailers() {
+        let changes = changes();
+        let info = PullCommitInfo { module_id: "xsiam", instance_name: "prod", tenant: "api-prod.example.com", changes: &changes };
+        let message = pull_commit_message(None, &info);
+
+        assert!(message.starts_with("Auto-commit: Updated Rule_A, Rule_B, Cleanup from XSIAM\n\n"));
+        assert!(message.ends_with(
+            "Gcgit-Module: xsiam\n\
+             Gcgit-Instance: prod\n\
+             Gcgit-Tenant: api-prod.example.com\n\
+             Gcgit-Changed: biocs added=1 modified=1 removed=0\n\
+             Gcgit-Changed: scripts added=0 modified=1 removed=0\n"
+        ));
+    }
+
+    #[test]
+    fn test_trailers_identify_pull_commits() {
+        let changes = changes();
+        let info = PullCommitInfo { module_id: "xsiam", instance_name: "prod", tenant: "t", changes: &changes };
+        let message = pull_commit_message(None, &info);
+
+        assert!(is_pull_commit(&message, "xsiam", "prod"));
+        assert!(!is_pull_commit(&message, "appsec", "prod"));
+        assert!(!is_pull_commit("Local edit", "xsiam", "prod"));
+    }
+
+    #[test]
+    fn test_default_subject_for_many_files() {
+        let mut changes = changes();
+        changes.push(("xsiam/scripts/Extra.yaml".to_string(), Delta::Added));
+        let info = PullCommitInfo { module_id: "xsiam", instance_name: "prod", tenant: "t", changes: &changes };
+        let message = pull_commit_message(None, &info);
+        assert!(message.starts_with("Auto-commit: Updated 4 files from XSIAM (Rule_A, Rule_B)\n\n"));
+    }
+
+    #[test]
+    fn test_author_for_user() {
+        assert_eq!(author_for_user("jane@example.com", "tenant.example.com").email, "jane@example.com");
+        let author = author_for_user("Jane Doe", "tenant.example.com");
+        assert_eq!(author.name, "Jane Doe");
+        assert_eq!(author.email, "Jane.Doe@tenant.example.com");
+    }
+
+    #[test]
+    fn test_template_placeholders() {
+        let changes = changes();
+        let info = PullCommitInfo { module_id: "xsiam", instance_name: "prod", tenant: "t", changes: &changes };
+        let message = pull_commit_message(Some("chore({module}): sync {count} objects on {instance}"), &info);
+        assert!(message.starts_with("chore(xsiam): sync 3 objects on prod\n\n"));
+    }
+}
@@ -0,0 +1,150 @@
+// SPDX-FileCopyrightText: GoCortexIO
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+// Side-by-side comparison of two instance directories
+// Objects are matched by name within each content type, and by ID where IDs are stable
+
+use anyhow::Result;
+use std::collections::BTreeMap;
+
+use crate::modules::{ContentTypeDefinition, Module};
+use crate::object_diff::{self, FieldChange};
+use crate::parser::YamlParser;
+use crate::types::XsiamObject;
+
+/// Differences between the two sides for one content type
+#[derive(Debug, Default)]
+pub struct ContentTypeComparison {
+    /// Objects present only in the left instance
+    pub only_left: Vec<XsiamObject>,
+    /// Objects present only in the right instance
+    pub only_right: Vec<XsiamObject>,
+    /// Matched objects whose content differs, with the changed fields from left to right
+    pub differing: Vec<(XsiamObject, XsiamObject, Vec<FieldChange>)>,
+    /// Number of matched objects that are logically equal
+    pub identical: usize,
+}
+
+impl ContentTypeComparison {
+    pub fn has_drift(&self) -> bool {
+        !self.only_left.is_empty() || !self.only_right.is_empty() || !self.differing.is_empty()
+    }
+}
+
+/// Load every object of a module from an instance directory, keyed by content type
+pub fn load_module_objects(yaml_parser: &YamlParser, instance_name: &str, module: &dyn Module) -> Result<BTreeMap<String, Vec<XsiamObject>>> {
+    let module_dir = format!("{instance_name}/{}", module.id());
+    let mut objects: BTreeMap<String, Vec<XsiamObject>> = BTreeMap::new();
+
+    for content_type in module.content_types() {
+        let files = yaml_parser.get_local_files(&module_dir, &[content_type.name])?;
+        let parsed = files.iter()
+            .map(|file| yaml_parser.parse_file(file))
+            .collect::<Result<Vec<_>>>()?;
+        objects.insert(content_type.name.to_string(), parsed);
+    }
+
+    Ok(objects)
+}
+
+/// Compare the objects of one content type from two instances
+/// Objects are matched by name, since tenants assign different IDs to the same object.
+/// Unnamed objects are matched by ID only when the content type's IDs are stable across tenants.
+pub fn compare_objects(yaml_parser: &YamlParser, content_def: &ContentTypeDefinition, left: Vec<XsiamObject>, right: Vec<XsiamObject>) -> Result<ContentTypeComparison> {
+    let mut comparison = ContentTypeComparison::default();
+    let mut unmatched_right: Vec<Option<XsiamObject>> = right.into_iter().map(Some).collect();
+    let mut unmatched_left = Vec::new();
+
+    // Match by name
+    for left_object in left {
+        let position = left_object.name.as_ref().and_then(|name| {
+            unmatched_right.iter()
+                .position(|candidate| candidate.as_ref().is_some_and(|r| r.name.as_ref() == Some(name)))
+        });
+        match position.and_then(|index| unmatched_right[index].take()) {
+            Some(right_object) => record_match(yaml_parser, &mut comparison, left_object, right_object)?,
+            None => unmatched_left.push(left_object),
+        }
+    }
+
+    // Match the rest by ID where IDs mean the same thing on both tenants
+    for left_object in unmatched_left {
+        let position = content_def.stable_ids.then(|| {
+            unmatched_right.iter()
+                .position(|candidate| candidate.as_ref().is_some_and(|r| r.id == left_object.id))
+        }).flatten();
+        match position.and_then(|index| unmatched_right[index].take()) {
+            Some(right_object) => record_match(yaml_parser, &mut comparison, left_object, right_object)?,
+            None => comparison.only_left.push(left_object),
+        }
+    }
+
+    comparison.only_right = unmatched_right.into_iter().flatten().collect();
+    Ok(comparison)
+}
+
+fn record_match(yaml_parser: &YamlParser, comparison: &mut ContentTypeComparison, left: XsiamObject, right: XsiamObject) -> Result<()> {
+    // IDs are tenant-specific, so compare as if the right object had the left ID
+    let mut aligned_right = right.clone();
+    aligned_right.id = left.id.clone();
+
+    if yaml_parser.objects_are_logically_equal(&left, &aligned_right)? {
+        comparison.identical += 1;
+    } else {
+        let fields = object_diff::field_changes(&left, &aligned_right);
+        comparison.differing.push((left, right, fields));
+    }
+    Ok(())
+}
+
+/// Display name for an object in comparison output
+pub fn display_name(object: &XsiamObject) -> String {
+    match &object.name {
+        Some(name) if !name.trim().is_empty() => name.clone(),
+        _ => format!("id {}", object.id),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::content_type_definition;
+    use serde_json::json;
+
+    fn rule(id: &str, name: &str, severity: &str) -> XsiamObject {
+        let mut object = XsiamObject::new(id.to_string(), name.to_string(), "biocs".to_string());
+        object.content.insert("severity".to_string(), json!(severity));
+        object
+    }
+
+    #[test]
+    fn test_compare_matches_by_name() {
+        let parser = YamlParser::new();
+        let left = vec![rule("1", "Same", "low"), rule("2", "Renumbered", "low"), rule("3", "Changed", "low"), rule("4", "Dev only", "low")];
+        let right = vec![rule("1", "Same", "low"), rule("20", "Renumbered", "low"), rule("3", "Changed", "high"), rule("4", "Prod only", "low")];
+
+        // Tenant-assigned IDs never pair objects with different names
+        let comparison = compare_objects(&parser, &content_type_definition("xsiam", "biocs"), left, right).unwrap();
+        assert_eq!(comparison.identical, 2);
+        assert_eq!(comparison.differing.len(), 1);
+        assert_eq!(comparison.differing[0].2[0].path, "severity");
+        assert_eq!(display_name(&comparison.only_left[0]), "Dev only");
+        assert_eq!(display_name(&comparison.only_right[0]), "Prod only");
+        assert!(comparison.has_drift());
+    }
+
+    #[test]
+    fn test_compare_falls_back_to_stable_ids() {
+        let parser = YamlParser::new();
+        let mut left = rule("okta", "", "low");
+        left.name = None;
+        let mut right = rule("okta", "", "high");
+        right.name = None;
+
+        let comparison = compare_objects(&parser, &content_type_definition("xsiam", "authentication_settings"), vec![left.clone()], vec![right.clone()]).unwrap();
+        assert_eq!(comparison.differing.len(), 1);
+
+        let comparison = compare_objects(&parser, &content_type_definition("xsiam", "biocs"), vec![left], vec![right]).unwrap();
+        assert_eq!((comparison.only_left.len(), comparison.only_right.len()), (1, 1));
+    }
+}
@@ -3,10 +3,14 @@
 
 use anyhow::{Result, Context};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::Path;
 use std::env;
-use crate::git_wrapper::GitWrapper;
+use crate::git_wrapper::{GitWrapper, RemoteCredentials};
+use crate::normalise::Normaliser;
+use crate::signing::{CommitSigner, SignatureVerifier, SigningFormat};
+use crate::templating::Templating;
 
 // Re-export ModuleConfig for public use
 pub use crate::modules::ModuleConfig;
@@ -47,11 +51,223 @@ pub struct ConfigFile {
     // New multi-module format (v2.0+)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub modules: Option<ModulesConfig>,
+
+    // Git repository behaviour for this instance
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git: Option<GitSettings>,
+
+    // Git remote the instance repository is published to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remote: Option<RemoteSettings>,
+
+    // Commit signing and verification
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signing: Option<SigningSettings>,
+
+    // Pull commit message format
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit: Option<CommitSettings>,
+
+    // Per-instance values for {{ var }} placeholders in object YAML
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vars: Option<BTreeMap<String, String>>,
+
+    // Placeholder behaviour on pull
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub templating: Option<TemplatingSettings>,
+
+    // Golden baseline this instance is checked against
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub baseline: Option<BaselineSettings>,
+
+    // How objects are laid out on disk
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub layout: Option<LayoutSettings>,
+
+    // Volatile field handling per content type, keyed by content type name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub normalise: Option<BTreeMap<String, NormaliseRules>>,
+
+    // Archive of raw API responses per pull
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub archive: Option<ArchiveSettings>,
+}
+
+/// Raw response archive settings from the [archive] block of config.toml
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct ArchiveSettings {
+    /// Save the raw API responses of every pull
+    #[serde(default)]
+    pub raw_responses: bool,
+    /// Archive directory, relative to the working directory; defaults to .gcgit/archive
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+}
+
+/// Normalisation rules from a [normalise.<content_type>] block of config.toml
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct NormaliseRules {
+    /// Fields removed from the object
+    #[serde(default)]
+    pub drop: Vec<String>,
+    /// Fields replaced by the empty value of their type
+    #[serde(default)]
+    pub zero: Vec<String>,
+    /// Fields moved into the object metadata, which is not compared
+    #[serde(default)]
+    pub metadata: Vec<String>,
+}
+
+/// File layout settings from the [layout] block of config.toml
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct LayoutSettings {
+    /// Write XQL queries to .xql files next to the object YAML
+    #[serde(default)]
+    pub xql_files: bool,
+    /// Keep object metadata in module/.metadata/<content_type>.yaml instead of each object file
+    #[serde(default)]
+    pub metadata_index: bool,
+}
+
+/// Baseline settings from the [baseline] block of config.toml
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct BaselineSettings {
+    /// Baseline directory, relative to the working directory
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+}
+
+/// Templating settings from the [templating] block of config.toml
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct TemplatingSettings {
+    /// Replace known [vars] values with their placeholders when pulling
+    #[serde(default)]
+    pub reverse_map: bool,
+}
+
+/// Commit message settings from the [commit] block of config.toml
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct CommitSettings {
+    /// Subject line template for pull commits; trailers are always appended
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+}
+
+/// Instance Git settings from the [git] block of config.toml
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct GitSettings {
+    /// Commit pulls onto platform/<module> branches instead of the checked-out branch
+    #[serde(default)]
+    pub platform_branches: bool,
+}
+
+/// Commit signing from the [signing] block of config.toml
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct SigningSettings {
+    #[serde(default)]
+    pub format: SigningFormat,
+    /// SSH private key file or GPG key ID used to sign gcgit commits
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    /// SSH allowed-signers file or GPG public keyring trusted for verification
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allowed_signers: Option<String>,
+    /// Refuse to push commits to the platform unless their signature verifies
+    #[serde(default)]
+    pub require_signed: bool,
+}
+
+/// Git remote from the [remote] block of config.toml
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RemoteSettings {
+    /// Remote URL (SSH, HTTPS or a local path)
+    pub url: String,
+    /// Remote name in the instance repository
+    #[serde(default = "default_remote_name")]
+    pub name: String,
+    /// Remote branch for the checked-out branch (defaults to the same name)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    #[serde(default)]
+    pub auth: RemoteAuth,
+    /// Private key file for key authentication
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ssh_key: Option<String>,
+    /// Passphrase for the private key, usually an environment variable reference
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ssh_passphrase: Option<String>,
+    /// Username for token authentication
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    /// Access token for token authentication, usually an environment variable reference
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+}
+
+/// How gcgit authenticates to the Git remote
+#[derive(Debug, Default, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RemoteAuth {
+    #[default]
+    SshAgent,
+    Key,
+    Token,
+}
+
+fn default_remote_name() -> String {
+    "origin".to_string()
+}
+
+impl RemoteSettings {
+    // Resolve configured credentials, expanding environment variable references
+    pub fn credentials(&self) -> Result<RemoteCredentials> {
+        match self.auth {
+            RemoteAuth::SshAgent => Ok(RemoteCredentials::SshAgent),
+            RemoteAuth::Key => {
+                let key = self.ssh_key.as_deref()
+                    .ok_or_else(|| anyhow::anyhow!("[remote] auth = \"key\" requires ssh_key"))?;
+                let passphrase = match &self.ssh_passphrase {
+                    Some(value) => Some(expand_env_vars(value)?).filter(|p| !p.is_empty()),
+                    None => None,
+                };
+                Ok(RemoteCredentials::SshKey { path: expand_home(&expand_env_vars(key)?), passphrase })
+            }
+            RemoteAuth::Token => {
+                let token = expand_env_vars(self.token.as_deref().unwrap_or_default())?;
+                if token.is_empty() {
+                    return Err(anyhow::anyhow!("[remote] auth = \"token\" requires a non-empty token"));
+                }
+                let username = self.username.clone().unwrap_or_else(|| "x-access-token".to_string());
+                Ok(RemoteCredentials::Token { username, token })
+            }
+        }
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct GlobalConfig {
     pub default_instance: Option<String>,
+
+    // Instances processed at once by --group commands
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_parallel: Option<usize>,
+
+    // Named instance groups for fleet commands
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub groups: BTreeMap<String, Vec<String>>,
+}
+
+impl GlobalConfig {
+    /// Member instances of a group
+    pub fn group_instances(&self, group: &str) -> Result<Vec<String>> {
+        let instances = self.groups.get(group).ok_or_else(|| anyhow::anyhow!(
+            "Group '{group}' not found. Define it under [groups] in .gcgit/global_config.toml"
+        ))?;
+        if instances.is_empty() {
+            return Err(anyhow::anyhow!("Group '{group}' has no instances"));
+        }
+        Ok(instances.clone())
+    }
 }
 
 pub struct ConfigManager;
@@ -61,8 +277,8 @@ impl ConfigManager {
         Self
     }
 
-    // Load configuration for a specific module in an instance
-    pub fn load_module_config(&self, instance_name: &str, module_id: &str) -> Result<ModuleConfig> {
+    // Load and parse an instance's config.toml
+    pub fn load_config_file(&self, instance_name: &str) -> Result<ConfigFile> {
         let config_path = format!("{instance_name}/config.toml");
         
         if !Path::new(&config_path).exists() {
@@ -74,8 +290,102 @@ impl ConfigManager {
         let config_content = fs::read_to_string(&config_path)
             .with_context(|| format!("Failed to read config file: {config_path}"))?;
 
-        let config: ConfigFile = toml::from_str(&config_content)
-            .with_context(|| format!("Failed to parse config file: {config_path}"))?;
+        toml::from_str(&config_content)
+            .with_context(|| format!("Failed to parse config file: {config_path}"))
+    }
+
+    // Load the [git] settings for an instance, using defaults if the block is absent
+    pub fn load_git_settings(&self, instance_name: &str) -> Result<GitSettings> {
+        Ok(self.load_config_file(instance_name)?.git.unwrap_or_default())
+    }
+
+    // Load the [commit] settings for an instance, using defaults if the block is absent
+    pub fn load_commit_settings(&self, instance_name: &str) -> Result<CommitSettings> {
+        Ok(self.load_config_file(instance_name)?.commit.unwrap_or_default())
+    }
+
+    // Build the templating for an instance from its [vars] table, expanding environment variables
+    pub fn load_templating(&self, instance_name: &str) -> Result<Templating> {
+        let vars = self.load_config_file(instance_name)?.vars.unwrap_or_default()
+            .into_iter()
+            .map(|(name, value)| Ok((name, expand_env_vars(&value)?)))
+            .collect::<Result<BTreeMap<_, _>>>()?;
+        Ok(Templating::new(instance_name, vars))
+    }
+
+    // Load the [templating] settings for an instance, using defaults if the block is absent
+    pub fn load_templating_settings(&self, instance_name: &str) -> Result<TemplatingSettings> {
+        Ok(self.load_config_file(instance_name)?.templating.unwrap_or_default())
+    }
+
+    // Load the [baseline] settings for an instance, using defaults if the block is absent
+    pub fn load_baseline_settings(&self, instance_name: &str) -> Result<BaselineSettings> {
+        Ok(self.load_config_file(instance_name)?.baseline.unwrap_or_default())
+    }
+
+    // Build the volatile field normaliser for an instance from its [normalise.*] blocks
+    // Rejects rules that move fields to metadata unless the metadata index is on
+    pub fn load_normaliser(&self, instance_name: &str) -> Result<Normaliser> {
+        let config = self.load_config_file(instance_name)?;
+        let normaliser = Normaliser::new(config.normalise.unwrap_or_default());
+        normaliser.check_layout(&config.layout.unwrap_or_default())?;
+        Ok(normaliser)
+    }
+
+    // Load the [archive] settings for an instance, using defaults if the block is absent
+    pub fn load_archive_settings(&self, instance_name: &str) -> Result<ArchiveSettings> {
+        Ok(self.load_config_file(instance_name)?.archive.unwrap_or_default())
+    }
+
+    // Load the [layout] settings for an instance, using defaults if the block is absent
+    pub fn load_layout_settings(&self, instance_name: &str) -> Result<LayoutSettings> {
+        Ok(self.load_config_file(instance_name)?.layout.unwrap_or_default())
+    }
+
+    // Load the [remote] settings for an instance, if a remote is configured
+    pub fn load_remote_settings(&self, instance_name: &str) -> Result<Option<RemoteSettings>> {
+        Ok(self.load_config_file(instance_name)?.remote)
+    }
+
+    // Build the commit signer for an instance, if a signing key is configured
+    pub fn load_commit_signer(&self, instance_name: &str) -> Result<Option<CommitSigner>> {
+        let Some(signing) = self.load_config_file(instance_name)?.signing else {
+            return Ok(None);
+        };
+        let Some(key) = signing.key else {
+            return Ok(None);
+        };
+
+        let key = match signing.format {
+            SigningFormat::Ssh => expand_home(&expand_env_vars(&key)?).to_string_lossy().into_owned(),
+            SigningFormat::Gpg => expand_env_vars(&key)?,
+        };
+        Ok(Some(CommitSigner { format: signing.format, key }))
+    }
+
+    // Build the verifier used to gate platform pushes, if signed commits are required
+    pub fn load_signature_verifier(&self, instance_name: &str) -> Result<Option<SignatureVerifier>> {
+        let signing = self.load_config_file(instance_name)?.signing.unwrap_or_default();
+        match signing.allowed_signers {
+            Some(path) => Ok(Some(SignatureVerifier {
+                format: signing.format,
+                allowed_signers: expand_home(&expand_env_vars(&path)?),
+            })),
+            None if signing.require_signed => Err(anyhow::anyhow!(
+                "[signing] require_signed = true needs allowed_signers in {instance_name}/config.toml"
+            )),
+            None => Ok(None),
+        }
+    }
+
+    // Whether platform pushes must come from verified commits
+    pub fn requires_signed_commits(&self, instance_name: &str) -> Result<bool> {
+        Ok(self.load_config_file(instance_name)?.signing.is_some_and(|s| s.require_signed))
+    }
+
+    // Load configuration for a specific module in an instance
+    pub fn load_module_config(&self, instance_name: &str, module_id: &str) -> Result<ModuleConfig> {
+        let config = self.load_config_file(instance_name)?;
 
         // Try new multi-module format first
         if let Some(modules) = &config.modules {
@@ -112,13 +422,14 @@ impl ConfigManager {
         ))
     }
 
-    #[allow(dead_code)]
     pub fn load_global_config(&self) -> Result<GlobalConfig> {
         let config_path = ".gcgit/global_config.toml";
         
         if !Path::new(config_path).exists() {
             return Ok(GlobalConfig {
                 default_instance: None,
+                max_parallel: None,
+                groups: BTreeMap::new(),
             });
         }
 
@@ -187,6 +498,16 @@ impl ConfigManager {
                     api_key_id: "${XSIAM_API_KEY_ID}".to_string(),
                 }),
             }),
+            git: None,
+            remote: None,
+            signing: None,
+            commit: None,
+            vars: None,
+            templating: None,
+            baseline: None,
+            layout: None,
+            normalise: None,
+            archive: None,
         };
 
         let config_content = toml::to_string_pretty(&config_template)
@@ -222,6 +543,13 @@ fn expand_env_vars(input: &str) -> Result<String> {
     }
 }
 
+fn expand_home(path: &str) -> std::path::PathBuf {
+    match (path.strip_prefix("~/"), env::var("HOME")) {
+        (Some(rest), Ok(home)) => Path::new(&home).join(rest),
+        _ => std::path::PathBuf::from(path),
+    }
+}
+
 fn resolve_with_fallback(value: &str, fallback_var: &str, field_label: &str, module_id: &str) -> Result<String> {
     let expanded = expand_env_vars(value)?;
     if !expanded.is_empty() {
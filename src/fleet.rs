@@ -0,0 +1,164 @@
+// SPDX-FileCopyrightText: GoCortexIO
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+// Running one command across a group of instances
+// Each instance runs in its own gcgit process so output and failures stay separate;
+// at most max_parallel processes run at once.
+
+use anyhow::{Context, Result};
+use std::collections::VecDeque;
+use std::path::Path;
+use std::process::Command;
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Default number of instances processed at once
+pub const DEFAULT_MAX_PARALLEL: usize = 4;
+
+/// Outcome of the command for one instance
+#[derive(Debug)]
+pub struct InstanceRun {
+    pub instance: String,
+    pub success: bool,
+    pub duration: Duration,
+    /// Combined standard output and error
+    pub output: String,
+    /// One-line summary for the fleet table
+    pub summary: String,
+}
+
+/// Arguments to re-run the current command for a single instance
+/// Removes --group (and its value); the caller appends --instance per instance.
+pub fn child_args(args: &[String]) -> Vec<String> {
+    let mut result = Vec::new();
+    let mut skip_value = false;
+    for arg in args {
+        if skip_value {
+            skip_value = false;
+        } else if arg == "--group" {
+            skip_value = true;
+        } else if !arg.starts_with("--group=") {
+            result.push(arg.clone());
+        }
+    }
+    result
+}
+
+/// Run `program args --instance <name>` for every instance
+/// `on_finish` is called as each instance completes; results are returned in instance order.
+pub fn run_across(program: &Path, args: &[String], instances: &[String], max_parallel: usize, on_finish: impl Fn(&InstanceRun)) -> Result<Vec<InstanceRun>> {
+    let queue: Arc<Mutex<VecDeque<(usize, String)>>> = Arc::new(Mutex::new(
+        instances.iter().cloned().enumerate().collect()
+    ));
+    let (sender, receiver) = mpsc::channel();
+    let workers = max_parallel.max(1).min(instances.len());
+
+    let mut handles = Vec::new();
+    for _ in 0..workers {
+        let queue = Arc::clone(&queue);
+        let sender = sender.clone();
+        let program = program.to_path_buf();
+        let args = args.to_vec();
+        handles.push(thread::spawn(move || {
+            while let Some((index, instance)) = queue.lock().ok().and_then(|mut queue| queue.pop_front()) {
+                let run = run_instance(&program, &args, &instance);
+                if sender.send((index, run)).is_err() {
+                    break;
+                }
+            }
+        }));
+    }
+    drop(sender);
+
+    let mut results: Vec<Option<InstanceRun>> = instances.iter().map(|_| None).collect();
+    for (index, run) in receiver {
+        let run = run?;
+        on_finish(&run);
+        results[index] = Some(run);
+    }
+    for handle in handles {
+        handle.join().map_err(|_| anyhow::anyhow!("Fleet worker thread panicked"))?;
+    }
+
+    Ok(results.into_iter().flatten().collect())
+}
+
+fn run_instance(program: &Path, args: &[String], instance: &str) -> Result<InstanceRun> {
+    let started = Instant::now();
+    let output = Command::new(program)
+        .args(args)
+        .arg("--instance")
+        .arg(instance)
+        .output()
+        .with_context(|| format!("Failed to run {} for instance {instance}", program.display()))?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+    let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+    let success = output.status.success();
+    let summary = summarise(success, &stdout, &stderr);
+
+    let mut combined = stdout;
+    if !stderr.trim().is_empty() {
+        if !combined.is_empty() && !combined.ends_with('\n') {
+            combined.push('\n');
+        }
+        combined.push_str(&stderr);
+    }
+
+    Ok(InstanceRun {
+        instance: instance.to_string(),
+        success,
+        duration: started.elapsed(),
+        output: combined,
+        summary,
+    })
+}
+
+/// Pick the line that best describes the run: the error for failures, else the last output line
+fn summarise(success: bool, stdout: &str, stderr: &str) -> String {
+    let last_line = |text: &str| text.lines().rev().find(|line| !line.trim().is_empty()).map(|line| line.trim().to_string());
+
+    if !success {
+        if let Some(error) = stderr.lines().find(|line| line.starts_with("Error: ")) {
+            return error.trim_start_matches("Error: ").to_string();
+        }
+        if let Some(line) = last_line(stderr) {
+            return line;
+        }
+    }
+    last_line(stdout).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_child_args_strip_group() {
+        let args: Vec<String> = ["xsiam", "pull", "--group", "emea", "--git-push"].iter().map(|s| s.to_string()).collect();
+        assert_eq!(child_args(&args), vec!["xsiam", "pull", "--git-push"]);
+
+        let args: Vec<String> = ["status", "--group=emea"].iter().map(|s| s.to_string()).collect();
+        assert_eq!(child_args(&args), vec!["status"]);
+    }
+
+    #[test]
+    fn test_run_across_collects_results_in_order() {
+        // sh receives "--instance" as $0 and the instance name as $1
+        let args = vec!["-c".to_string(), "echo \"checked $1\"; if [ \"$1\" = bad ]; then echo 'Error: broken' >&2; exit 1; fi".to_string()];
+        let instances: Vec<String> = ["a", "bad", "c"].iter().map(|s| s.to_string()).collect();
+
+        let finished = Mutex::new(0);
+        let runs = run_across(Path::new("sh"), &args, &instances, 2, |_| *finished.lock().unwrap() += 1).unwrap();
+
+        assert_eq!(*finished.lock().unwrap(), 3);
+        let names: Vec<&str> = runs.iter().map(|run| run.instance.as_str()).collect();
+        assert_eq!(names, vec!["a", "bad", "c"]);
+        assert!(runs[0].success);
+        assert_eq!(runs[0].summary, "checked a");
+        assert!(!runs[1].success);
+        assert_eq!(runs[1].summary, "broken");
+    }
+}
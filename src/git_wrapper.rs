@@ -2,11 +2,66 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
 use anyhow::{Result, Context};
-use git2::{Repository, Status, StatusOptions, Signature};
+use git2::{Cred, Delta, DiffOptions, FetchOptions, FileMode, ObjectType, Oid, PushOptions, RemoteCallbacks, Repository, Status, StatusOptions, Signature};
+use git2::build::{CheckoutBuilder, TreeUpdateBuilder};
+
+use crate::metadata_index;
+use crate::signing::{CommitSigner, SignatureVerifier};
+
+/// A path relative to the repository root and how it changed
+pub type FileChange = (String, Delta);
+
+/// Identity recorded as the author of a commit
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct CommitAuthor {
+    pub name: String,
+    pub email: String,
+}
+
+/// Branch name prefix for branches that mirror platform state
+pub const PLATFORM_BRANCH_PREFIX: &str = "platform/";
 
 
 pub struct GitWrapper {
     repo: Repository,
+    signer: Option<CommitSigner>,
+}
+
+/// A file edited locally before a pull: (path, content at HEAD, content in the worktree)
+/// A missing content means the file did not exist there.
+pub type LocalEdit = (String, Option<String>, Option<String>);
+
+/// How a path in the worktree changes when restored from a past revision
+#[derive(Debug, Clone, PartialEq)]
+pub enum RestoreAction {
+    /// File content is written from the revision
+    Restored,
+    /// File did not exist at the revision and is removed
+    Removed,
+}
+
+/// Outcome of merging a branch into the checked-out branch
+#[derive(Debug, Clone, PartialEq)]
+pub enum BranchMergeResult {
+    /// Nothing to merge
+    UpToDate,
+    /// Checked-out branch was moved forward to the merged branch
+    FastForward,
+    /// A merge commit was created
+    Merged,
+    /// Merge stopped with conflicts in these paths; resolve and commit with Git
+    Conflicts(Vec<String>),
+}
+
+/// Credentials offered to a Git remote when pushing
+#[derive(Debug, Clone)]
+pub enum RemoteCredentials {
+    /// Keys loaded in the running SSH agent
+    SshAgent,
+    /// Private key file, optionally protected by a passphrase
+    SshKey { path: std::path::PathBuf, passphrase: Option<String> },
+    /// HTTPS username and access token
+    Token { username: String, token: String },
 }
 
 impl GitWrapper {
@@ -15,7 +70,7 @@ impl GitWrapper {
             .or_else(|_| Repository::init(path))
             .context("Failed to open or initialise Git repository")?;
 
-        Ok(Self { repo })
+        Ok(Self { repo, signer: None })
     }
 
     pub fn new_for_instance(instance_name: &str) -> Result<Self> {
@@ -30,7 +85,7 @@ impl GitWrapper {
             .or_else(|_| Repository::init(instance_name))
             .context("Failed to open or initialise Git repository for instance")?;
 
-        Ok(Self { repo })
+        Ok(Self { repo, signer: None })
     }
 
     // Future diff/push features - detect changes from main branch
@@ -164,11 +219,11 @@ impl GitWrapper {
         Ok(false)
     }
 
-    /// Check if specific files have changes after adding them to staging
-    /// Returns (has_changes, count_of_changed_files, list_of_changed_files)
-    pub fn has_changes_after_add(&self, files: &[String]) -> Result<(bool, usize, Vec<String>)> {
+    /// Stage written and removed files, then report (has changes, change count, changed files with how each changed)
+    pub fn has_changes_after_add(&self, files: &[String], removed: &[String]) -> Result<(bool, usize, Vec<FileChange>)> {
         // Add files to staging
         self.add_files(files)?;
+        self.remove_files(removed)?;
         
         // Check if there are any staged changes and collect them
         let statuses = self.repo.statuses(None)
@@ -178,12 +233,17 @@ impl GitWrapper {
         for status in statuses.iter() {
             let status_flags = status.status();
             // Check for staged changes (INDEX_NEW, INDEX_MODIFIED, etc.)
-            if status_flags.contains(Status::INDEX_NEW) || 
-               status_flags.contains(Status::INDEX_MODIFIED) ||
-               status_flags.contains(Status::INDEX_DELETED) {
-                if let Some(path) = status.path() {
-                    changed_files.push(path.to_string());
-                }
+            let delta = if status_flags.contains(Status::INDEX_NEW) {
+                Delta::Added
+            } else if status_flags.contains(Status::INDEX_MODIFIED) {
+                Delta::Modified
+            } else if status_flags.contains(Status::INDEX_DELETED) {
+                Delta::Deleted
+            } else {
+                continue;
+            };
+            if let Some(path) = status.path() {
+                changed_files.push((path.to_string(), delta));
             }
         }
 
@@ -206,7 +266,36 @@ impl GitWrapper {
         Ok(())
     }
 
-    pub fn commit(&self, message: &str) -> Result<()> {
+    /// Stage the removal of files deleted from the worktree
+    pub fn remove_files(&self, files: &[String]) -> Result<()> {
+        if files.is_empty() {
+            return Ok(());
+        }
+        let mut index = self.repo.index()
+            .context("Failed to get repository index")?;
+
+        for file in files {
+            index.remove_path(std::path::Path::new(file))
+                .with_context(|| format!("Failed to remove file from index: {file}"))?;
+        }
+
+        index.write()
+            .context("Failed to write index")?;
+
+        Ok(())
+    }
+
+    /// List files tracked in the index under any of the given directories
+    pub fn tracked_files_under(&self, dirs: &[String]) -> Result<Vec<String>> {
+        let index = self.repo.index()
+            .context("Failed to get repository index")?;
+        Ok(index.iter()
+            .map(|entry| String::from_utf8_lossy(&entry.path).into_owned())
+            .filter(|path| dirs.iter().any(|dir| path.starts_with(&format!("{}/", dir.trim_end_matches('/')))))
+            .collect())
+    }
+
+    pub fn commit(&self, message: &str) -> Result<Oid> {
         let mut index = self.repo.index()
             .context("Failed to get repository index")?;
         let tree_id = index.write_tree()
@@ -214,48 +303,819 @@ impl GitWrapper {
         let tree = self.repo.find_tree(tree_id)
             .context("Failed to find tree")?;
 
-        // Try to get signature from Git config, fallback to default if not available
-        let signature = match self.repo.signature() {
-            Ok(sig) => sig,
-            Err(_) => {
-                // Fallback to gcgit default signature if Git config is not set
-                Signature::now("gcgit", "gcgit@localhost")
-                    .context("Failed to create fallback signature")?
-            }
-        };
+        let signature = self.default_signature()?;
 
         // Handle both initial commit and subsequent commits
-        match self.repo.head() {
+        let commit_id = match self.repo.head() {
             Ok(head) => {
                 // Repository has commits, create commit with parent
                 let parent_commit = head.peel_to_commit()
                     .context("Failed to peel HEAD to commit")?;
-                self.repo.commit(
-                    Some("HEAD"),
-                    &signature,
-                    &signature,
-                    message,
-                    &tree,
-                    &[&parent_commit],
-                ).context("Failed to create commit")?;
+                self.create_commit("HEAD", &signature, &signature, message, &tree, &[&parent_commit])
+                    .context("Failed to create commit")?
             }
             Err(e) if e.code() == git2::ErrorCode::UnbornBranch => {
                 // Repository is empty, create initial commit
-                self.repo.commit(
-                    Some("HEAD"),
-                    &signature,
-                    &signature,
-                    message,
-                    &tree,
-                    &[],
-                ).context("Failed to create initial commit")?;
+                self.create_commit("HEAD", &signature, &signature, message, &tree, &[])
+                    .context("Failed to create initial commit")?
             }
             Err(e) => return Err(anyhow::anyhow!("Failed to get HEAD reference: {e}")),
+        };
+
+        Ok(commit_id)
+    }
+
+    /// Commit only the given staged paths on top of HEAD, leaving other staged changes staged
+    /// The author defaults to the committer when not given.
+    pub fn commit_staged_paths(&self, paths: &[String], message: &str, author: Option<&CommitAuthor>) -> Result<Oid> {
+        let index = self.repo.index()
+            .context("Failed to get repository index")?;
+        let parent = match self.repo.head() {
+            Ok(head) => Some(head.peel_to_commit().context("Failed to peel HEAD to commit")?),
+            Err(e) if e.code() == git2::ErrorCode::UnbornBranch => None,
+            Err(e) => return Err(anyhow::anyhow!("Failed to get HEAD reference: {e}")),
+        };
+        let base_tree = match &parent {
+            Some(commit) => commit.tree().context("Failed to get HEAD tree")?,
+            None => {
+                let empty_tree_id = self.repo.treebuilder(None)
+                    .and_then(|builder| builder.write())
+                    .context("Failed to create empty tree")?;
+                self.repo.find_tree(empty_tree_id).context("Failed to find empty tree")?
+            }
+        };
+
+        let mut update = TreeUpdateBuilder::new();
+        for path in paths {
+            match index.get_path(std::path::Path::new(path), 0) {
+                Some(entry) => {
+                    let mode = if entry.mode == u32::from(FileMode::BlobExecutable) { FileMode::BlobExecutable } else { FileMode::Blob };
+                    update.upsert(path.as_str(), entry.id, mode);
+                }
+                None => {
+                    update.remove(path.as_str());
+                }
+            }
+        }
+        let tree_id = update.create_updated(&self.repo, &base_tree)
+            .context("Failed to build commit tree")?;
+        let tree = self.repo.find_tree(tree_id)
+            .context("Failed to find tree")?;
+
+        let committer = self.default_signature()?;
+        let author = self.author_signature(author, &committer)?;
+        let parents: Vec<&git2::Commit> = parent.iter().collect();
+        self.create_commit("HEAD", &author, &committer, message, &tree, &parents)
+            .context("Failed to create commit")
+    }
+
+    /// Author signature for a commit, timestamped with the committer's time
+    fn author_signature(&self, author: Option<&CommitAuthor>, committer: &Signature<'static>) -> Result<Signature<'static>> {
+        match author {
+            Some(author) => Signature::new(&author.name, &author.email, &committer.when())
+                .with_context(|| format!("Invalid author identity: {} <{}>", author.name, author.email)),
+            None => Ok(committer.clone()),
+        }
+    }
+
+    /// Find the most recent commit reachable from a revision whose message satisfies a predicate
+    /// Returns the commit ID and commit time, or None if no commit matches or the revision does not exist.
+    pub fn find_latest_commit(&self, rev: &str, matches: impl Fn(&str) -> bool) -> Result<Option<(Oid, chrono::DateTime<chrono::Utc>)>> {
+        let start = match self.repo.revparse_single(rev).and_then(|object| object.peel_to_commit()) {
+            Ok(commit) => commit.id(),
+            Err(_) => return Ok(None),
+        };
+
+        let mut revwalk = self.repo.revwalk().context("Failed to walk history")?;
+        revwalk.set_sorting(git2::Sort::TIME).context("Failed to sort history")?;
+        revwalk.push(start).context("Failed to walk history")?;
+
+        for oid in revwalk {
+            let oid = oid.context("Failed to read history")?;
+            let commit = self.repo.find_commit(oid).context("Failed to find commit")?;
+            if matches(commit.message().unwrap_or_default()) {
+                let time = chrono::DateTime::from_timestamp(commit.time().seconds(), 0)
+                    .ok_or_else(|| anyhow::anyhow!("Commit {oid} has an invalid timestamp"))?;
+                return Ok(Some((oid, time)));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Attach a note to a commit under the given notes reference, replacing any existing note
+    pub fn add_note(&self, notes_ref: &str, commit_id: Oid, note: &str) -> Result<()> {
+        let signature = self.default_signature()?;
+        self.repo.note(&signature, &signature, Some(notes_ref), commit_id, note, true)
+            .with_context(|| format!("Failed to add note to {commit_id}"))?;
+        Ok(())
+    }
+
+    /// Read the note attached to a commit under the given notes reference
+    #[cfg(test)]
+    pub fn read_note(&self, notes_ref: &str, commit_id: Oid) -> Option<String> {
+        self.repo.find_note(Some(notes_ref), commit_id).ok()
+            .and_then(|note| note.message().map(|message| message.to_string()))
+    }
+
+    /// Check whether a reference exists
+    pub fn reference_exists(&self, name: &str) -> bool {
+        self.repo.find_reference(name).is_ok()
+    }
+
+    /// Sign commits created from now on with the given signer (None creates unsigned commits)
+    pub fn set_signer(&mut self, signer: Option<CommitSigner>) {
+        self.signer = signer;
+    }
+
+    /// Create a commit and move a reference to it, signing it when a signer is configured
+    /// "HEAD" updates the checked-out branch, including an unborn one.
+    fn create_commit(&self, update_ref: &str, author: &Signature, committer: &Signature, message: &str, tree: &git2::Tree, parents: &[&git2::Commit]) -> Result<Oid> {
+        let Some(signer) = &self.signer else {
+            return self.repo.commit(Some(update_ref), author, committer, message, tree, parents)
+                .map_err(|e| anyhow::anyhow!("{e}"));
+        };
+
+        let buffer = self.repo.commit_create_buffer(author, committer, message, tree, parents)
+            .context("Failed to build commit")?;
+        let payload = buffer.as_str()
+            .ok_or_else(|| anyhow::anyhow!("Commit content is not valid UTF-8"))?;
+        let commit_signature = signer.sign(payload)?;
+        let commit_id = self.repo.commit_signed(payload, &commit_signature, None)
+            .context("Failed to write signed commit")?;
+
+        let ref_name = if update_ref == "HEAD" {
+            match self.repo.find_reference("HEAD")?.symbolic_target() {
+                Some(target) => target.to_string(),
+                None => {
+                    self.repo.set_head_detached(commit_id).context("Failed to update HEAD")?;
+                    return Ok(commit_id);
+                }
+            }
+        } else {
+            update_ref.to_string()
+        };
+        let summary = message.lines().next().unwrap_or_default();
+        self.repo.reference(&ref_name, commit_id, true, &format!("commit: {summary}"))
+            .with_context(|| format!("Failed to update {ref_name}"))?;
+
+        Ok(commit_id)
+    }
+
+    /// Verify the signature of the commit at a revision, returning the signer identity
+    pub fn verify_commit(&self, rev: &str, verifier: &SignatureVerifier) -> Result<String> {
+        let commit = self.repo.revparse_single(rev)
+            .and_then(|object| object.peel_to_commit())
+            .with_context(|| format!("Revision '{rev}' not found"))?;
+        let (signature, payload) = match self.repo.extract_signature(&commit.id(), None) {
+            Ok(extracted) => extracted,
+            Err(e) if e.code() == git2::ErrorCode::NotFound => {
+                return Err(anyhow::anyhow!("Commit {} is not signed", commit.id()));
+            }
+            Err(e) => return Err(anyhow::anyhow!("Failed to read signature of {}: {e}", commit.id())),
+        };
+
+        let signature = signature.as_str()
+            .ok_or_else(|| anyhow::anyhow!("Signature of {} is not valid UTF-8", commit.id()))?;
+        let payload = payload.as_str()
+            .ok_or_else(|| anyhow::anyhow!("Commit {} is not valid UTF-8", commit.id()))?;
+
+        verifier.verify(signature, payload)
+            .with_context(|| format!("Commit {} failed signature verification", commit.id()))
+    }
+
+    /// List tracked files under a directory that have uncommitted edits (staged or unstaged)
+    /// Untracked files are ignored since pull only overwrites files it previously wrote.
+    /// An empty prefix checks the whole repository. Metadata index files are left out, since they
+    /// hold platform state that every pull rewrites.
+    pub fn get_locally_modified_files(&self, dir_prefix: &str) -> Result<Vec<String>> {
+        let prefix = if dir_prefix.is_empty() {
+            String::new()
+        } else {
+            format!("{}/", dir_prefix.trim_end_matches('/'))
+        };
+        let mut modified_files = Vec::new();
+
+        for (path, status) in self.get_repository_status()? {
+            if path.starts_with(&prefix) && !metadata_index::is_index_path(&path) &&
+               (status.contains(Status::WT_MODIFIED) ||
+                status.contains(Status::WT_DELETED) ||
+                status.contains(Status::INDEX_MODIFIED) ||
+                status.contains(Status::INDEX_NEW) ||
+                status.contains(Status::INDEX_DELETED)) {
+                modified_files.push(path);
+            }
         }
 
+        Ok(modified_files)
+    }
+
+    /// Stash uncommitted changes to the given files, leaving all other edits in place
+    /// The stash entry is built here because libgit2 resets the whole worktree when stashing paths.
+    /// Returns false if there was nothing to stash.
+    pub fn stash_local_changes(&mut self, paths: &[String], message: &str) -> Result<bool> {
+        if paths.is_empty() {
+            return Ok(false);
+        }
+        let head = self.repo.head()
+            .and_then(|head| head.peel_to_commit())
+            .context("Cannot stash without a commit to stash on")?;
+        let head_tree = head.tree().context("Failed to read HEAD tree")?;
+        let workdir = self.repo.workdir()
+            .ok_or_else(|| anyhow::anyhow!("Repository has no working directory"))?
+            .to_path_buf();
+        let index = self.repo.index().context("Failed to get repository index")?;
+
+        // Index commit: HEAD with the staged state of the paths
+        let mut index_update = TreeUpdateBuilder::new();
+        // Worktree commit: HEAD with the worktree state of the paths
+        let mut worktree_update = TreeUpdateBuilder::new();
+        for path in paths {
+            let staged = index.get_path(std::path::Path::new(path), 0);
+            let mode = match &staged {
+                Some(entry) if entry.mode == u32::from(FileMode::BlobExecutable) => FileMode::BlobExecutable,
+                _ => FileMode::Blob,
+            };
+            match &staged {
+                Some(entry) => index_update.upsert(path.as_str(), entry.id, mode),
+                None => index_update.remove(path.as_str()),
+            };
+            let full_path = workdir.join(path);
+            if full_path.exists() {
+                let blob_id = self.repo.blob_path(&full_path)
+                    .with_context(|| format!("Failed to read {}", full_path.display()))?;
+                worktree_update.upsert(path.as_str(), blob_id, mode);
+            } else {
+                worktree_update.remove(path.as_str());
+            }
+        }
+
+        let branch = self.current_branch().unwrap_or_else(|| "(no branch)".to_string());
+        let signature = self.default_signature()?;
+        let index_tree = self.repo.find_tree(index_update.create_updated(&self.repo, &head_tree)?)?;
+        let index_commit = self.repo.find_commit(self.repo.commit(None, &signature, &signature, &format!("index on {branch}: {message}"), &index_tree, &[&head])?)?;
+        let worktree_tree = self.repo.find_tree(worktree_update.create_updated(&self.repo, &head_tree)?)?;
+        if worktree_tree.id() == head_tree.id() && index_tree.id() == head_tree.id() {
+            return Ok(false);
+        }
+        let stash_message = format!("On {branch}: {message}");
+        let stash_commit = self.repo.commit(None, &signature, &signature, &stash_message, &worktree_tree, &[&head, &index_commit])
+            .context("Failed to create stash commit")?;
+        // Stash entries are read from the reflog, which is not kept for refs/stash by default
+        self.repo.reference_ensure_log("refs/stash")
+            .and_then(|()| self.repo.reference("refs/stash", stash_commit, true, &stash_message))
+            .context("Failed to update stash")?;
+
+        // Only the stashed paths go back to HEAD
+        self.restore_from_revision("HEAD", paths)?;
+        Ok(true)
+    }
+
+    /// Re-apply the most recent stash and drop it
+    /// If the stash does not apply cleanly it is left in place for manual recovery.
+    pub fn pop_stashed_changes(&mut self) -> Result<()> {
+        self.repo.stash_pop(0, None)
+            .context("Failed to re-apply stashed changes")
+    }
+
+    /// Reset the index entries of files to HEAD, keeping the worktree as it is
+    /// Files not in HEAD leave the index; tracked files go back to their committed content.
+    pub fn unstage_paths(&self, paths: &[String]) -> Result<()> {
+        if paths.is_empty() {
+            return Ok(());
+        }
+        let head = self.repo.head().ok()
+            .and_then(|head| head.peel(ObjectType::Commit).ok());
+        self.repo.reset_default(head.as_ref(), paths.iter())
+            .context("Failed to unstage files")
+    }
+
+    /// Backup file for local edits set aside during a pull of a module, inside the .git directory
+    pub fn local_edits_backup_path(&self, module_id: &str) -> std::path::PathBuf {
+        self.repo.path().join("gcgit").join(format!("local-edits-{module_id}.json"))
+    }
+
+    /// Read what an interrupted pull set aside, if a backup exists
+    pub fn read_local_edits_backup(&self, module_id: &str) -> Result<Option<Vec<LocalEdit>>> {
+        let path = self.local_edits_backup_path(module_id);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let edits = serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
+        Ok(Some(edits))
+    }
+
+    /// Save local edits to disk before they are removed from the worktree
+    pub fn write_local_edits_backup(&self, module_id: &str, edits: &[LocalEdit]) -> Result<()> {
+        let path = self.local_edits_backup_path(module_id);
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create {}", dir.display()))?;
+        }
+        let content = serde_json::to_string(edits).context("Failed to serialise local edits")?;
+        std::fs::write(&path, content)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// Remove the local edits backup once the edits are back in the worktree
+    pub fn remove_local_edits_backup(&self, module_id: &str) -> Result<()> {
+        let path = self.local_edits_backup_path(module_id);
+        if path.exists() {
+            std::fs::remove_file(&path)
+                .with_context(|| format!("Failed to remove {}", path.display()))?;
+        }
         Ok(())
     }
 
+    /// Read a file's content as it was at the given revision
+    /// Returns None if the file did not exist at that revision
+    pub fn read_file_at_revision(&self, rev: &str, path: &str) -> Result<Option<String>> {
+        let tree = match self.repo.revparse_single(rev) {
+            Ok(object) => object.peel_to_tree()
+                .with_context(|| format!("Revision '{rev}' does not point to a tree"))?,
+            Err(e) if matches!(e.code(), git2::ErrorCode::NotFound | git2::ErrorCode::UnbornBranch) => return Ok(None),
+            Err(e) => return Err(anyhow::anyhow!("Unknown revision '{rev}': {e}")),
+        };
+
+        let entry = match tree.get_path(std::path::Path::new(path)) {
+            Ok(entry) => entry,
+            Err(e) if e.code() == git2::ErrorCode::NotFound => return Ok(None),
+            Err(e) => return Err(anyhow::anyhow!("Failed to look up {path} at {rev}: {e}")),
+        };
+        let blob = entry.to_object(&self.repo)
+            .and_then(|object| object.peel_to_blob())
+            .with_context(|| format!("Failed to read {path} at {rev}"))?;
+
+        Ok(Some(String::from_utf8_lossy(blob.content()).into_owned()))
+    }
+
+    /// List the paths a restore from the given revision would change, without touching the worktree
+    pub fn preview_restore(&self, rev: &str, paths: &[String]) -> Result<Vec<(String, RestoreAction)>> {
+        let target_tree = self.repo.revparse_single(rev)
+            .with_context(|| format!("Unknown revision: {rev}"))?
+            .peel_to_tree()
+            .with_context(|| format!("Revision '{rev}' does not point to a tree"))?;
+
+        let mut diff_options = DiffOptions::new();
+        for path in paths {
+            diff_options.pathspec(path);
+        }
+        let diff = self.repo.diff_tree_to_workdir_with_index(Some(&target_tree), Some(&mut diff_options))
+            .context("Failed to compare revision with worktree")?;
+
+        let mut changes = Vec::new();
+        for delta in diff.deltas() {
+            // The revision is the old side of the diff, so files "added" in the
+            // worktree are the ones that did not exist at the revision
+            let action = match delta.status() {
+                Delta::Added => RestoreAction::Removed,
+                Delta::Deleted | Delta::Modified | Delta::Typechange => RestoreAction::Restored,
+                _ => continue,
+            };
+            let path = delta.new_file().path()
+                .or_else(|| delta.old_file().path())
+                .and_then(|p| p.to_str());
+            if let Some(path) = path {
+                changes.push((path.to_string(), action));
+            }
+        }
+
+        Ok(changes)
+    }
+
+    /// Check files out of a past revision into the worktree and index
+    /// Paths are relative to the repository root; an empty list restores every tracked file.
+    /// Returns each path that changed along with whether it was restored or removed.
+    pub fn restore_from_revision(&self, rev: &str, paths: &[String]) -> Result<Vec<(String, RestoreAction)>> {
+        let changes = self.preview_restore(rev, paths)?;
+        if changes.is_empty() {
+            return Ok(changes);
+        }
+
+        let target = self.repo.revparse_single(rev)
+            .with_context(|| format!("Unknown revision: {rev}"))?;
+        let mut checkout = CheckoutBuilder::new();
+        checkout.force();
+        for path in paths {
+            checkout.path(path);
+        }
+        self.repo.checkout_tree(&target, Some(&mut checkout))
+            .with_context(|| format!("Failed to check out files from {rev}"))?;
+
+        // Checkout only removes files tracked at HEAD, so clear anything staged since
+        let workdir = self.repo.workdir()
+            .ok_or_else(|| anyhow::anyhow!("Repository has no working directory"))?;
+        let mut index = self.repo.index()
+            .context("Failed to get repository index")?;
+        for (path, action) in &changes {
+            if *action == RestoreAction::Removed {
+                let full_path = workdir.join(path);
+                if full_path.exists() {
+                    std::fs::remove_file(&full_path)
+                        .with_context(|| format!("Failed to remove {}", full_path.display()))?;
+                }
+                if index.get_path(std::path::Path::new(path), 0).is_some() {
+                    index.remove_path(std::path::Path::new(path))
+                        .with_context(|| format!("Failed to remove {path} from index"))?;
+                }
+            }
+        }
+        index.write()
+            .context("Failed to write index")?;
+
+        Ok(changes)
+    }
+
+    /// Name of the checked-out branch, or None for a detached HEAD
+    pub fn current_branch(&self) -> Option<String> {
+        let head = self.repo.find_reference("HEAD").ok()?;
+        head.symbolic_target()
+            .and_then(|target| target.strip_prefix("refs/heads/"))
+            .map(|name| name.to_string())
+    }
+
+    /// ID of the commit HEAD points at, if there is one
+    pub fn head_commit_id(&self) -> Option<Oid> {
+        self.repo.head().ok()?.peel_to_commit().ok().map(|commit| commit.id())
+    }
+
+    /// Check whether a local branch exists
+    pub fn branch_exists(&self, branch: &str) -> bool {
+        self.repo.find_reference(&format!("refs/heads/{branch}")).is_ok()
+    }
+
+    /// List local branches whose names start with the given prefix
+    pub fn branches_with_prefix(&self, prefix: &str) -> Result<Vec<String>> {
+        let mut names = Vec::new();
+        for branch in self.repo.branches(Some(git2::BranchType::Local)).context("Failed to list branches")? {
+            let (branch, _) = branch.context("Failed to read branch")?;
+            if let Some(name) = branch.name().ok().flatten() {
+                if name.starts_with(prefix) {
+                    names.push(name.to_string());
+                }
+            }
+        }
+        names.sort();
+        Ok(names)
+    }
+
+    /// Create a local branch at a commit
+    pub fn create_branch(&self, branch: &str, commit_id: Oid) -> Result<()> {
+        let commit = self.repo.find_commit(commit_id)
+            .with_context(|| format!("Failed to find commit {commit_id}"))?;
+        self.repo.branch(branch, &commit, false)
+            .with_context(|| format!("Failed to create branch '{branch}'"))?;
+        Ok(())
+    }
+
+    /// Commit the branch would build on: its tip, or None when the branch does not exist yet
+    /// HEAD is never used, so local commits cannot leak into a branch that records platform state.
+    fn branch_base_commit(&self, branch: &str) -> Result<Option<git2::Commit<'_>>> {
+        match self.repo.find_reference(&format!("refs/heads/{branch}")) {
+            Ok(reference) => Ok(Some(reference.peel_to_commit().context("Failed to peel branch to commit")?)),
+            Err(_) => Ok(None),
+        }
+    }
+
+    /// List which of the given files are new or differ from their content on a branch
+    /// Files are (path relative to repository root, content) pairs; the worktree is not used.
+    pub fn changed_files_on_branch(&self, branch: &str, files: &[(String, Vec<u8>)]) -> Result<Vec<FileChange>> {
+        let base_tree = match self.branch_base_commit(branch)? {
+            Some(commit) => Some(commit.tree().context("Failed to get branch tree")?),
+            None => None,
+        };
+
+        let mut changed = Vec::new();
+        for (path, content) in files {
+            let new_id = Oid::hash_object(ObjectType::Blob, content)
+                .context("Failed to hash file content")?;
+            let existing_id = base_tree.as_ref()
+                .and_then(|tree| tree.get_path(std::path::Path::new(path)).ok())
+                .map(|entry| entry.id());
+            match existing_id {
+                None => changed.push((path.clone(), Delta::Added)),
+                Some(id) if id != new_id => changed.push((path.clone(), Delta::Modified)),
+                Some(_) => {}
+            }
+        }
+
+        Ok(changed)
+    }
+
+    /// List files on a branch under any of the given directories
+    /// A missing branch has no files.
+    pub fn branch_files_under(&self, branch: &str, dirs: &[String]) -> Result<Vec<String>> {
+        let Some(commit) = self.branch_base_commit(branch)? else {
+            return Ok(Vec::new());
+        };
+        let tree = commit.tree().context("Failed to get branch tree")?;
+
+        let mut files = Vec::new();
+        for dir in dirs {
+            let dir = dir.trim_end_matches('/');
+            let Ok(entry) = tree.get_path(std::path::Path::new(dir)) else {
+                continue;
+            };
+            let Ok(subtree) = self.repo.find_tree(entry.id()) else {
+                continue;
+            };
+            subtree.walk(git2::TreeWalkMode::PreOrder, |root, entry| {
+                if entry.kind() == Some(ObjectType::Blob) {
+                    if let Some(name) = entry.name() {
+                        files.push(format!("{dir}/{root}{name}"));
+                    }
+                }
+                git2::TreeWalkResult::Ok
+            }).with_context(|| format!("Failed to list {dir} on '{branch}'"))?;
+        }
+        Ok(files)
+    }
+
+    /// Commit files directly onto a branch without touching the worktree or index
+    /// Files are written or, when listed in `removed`, deleted from the branch.
+    /// A missing branch is created with a parentless first commit.
+    pub fn commit_files_to_branch(&self, branch: &str, files: &[(String, Vec<u8>)], removed: &[String], message: &str, author: Option<&CommitAuthor>) -> Result<Oid> {
+        if self.current_branch().as_deref() == Some(branch) {
+            return Err(anyhow::anyhow!(
+                "Branch '{branch}' is checked out; switch to your working branch before pulling"
+            ));
+        }
+
+        let parent = self.branch_base_commit(branch)?;
+        let base_tree = match &parent {
+            Some(commit) => commit.tree().context("Failed to get branch tree")?,
+            None => {
+                let empty_tree_id = self.repo.treebuilder(None)
+                    .and_then(|builder| builder.write())
+                    .context("Failed to create empty tree")?;
+                self.repo.find_tree(empty_tree_id).context("Failed to find empty tree")?
+            }
+        };
+
+        let mut update = TreeUpdateBuilder::new();
+        for (path, content) in files {
+            let blob_id = self.repo.blob(content)
+                .with_context(|| format!("Failed to write blob for {path}"))?;
+            update.upsert(path.as_str(), blob_id, FileMode::Blob);
+        }
+        for path in removed {
+            update.remove(path.as_str());
+        }
+        let tree_id = update.create_updated(&self.repo, &base_tree)
+            .context("Failed to build branch tree")?;
+        let tree = self.repo.find_tree(tree_id)
+            .context("Failed to find tree")?;
+
+        let committer = self.default_signature()?;
+        let author = self.author_signature(author, &committer)?;
+        let parents: Vec<&git2::Commit> = parent.iter().collect();
+        let commit_id = self.create_commit(&format!("refs/heads/{branch}"), &author, &committer, message, &tree, &parents)
+            .with_context(|| format!("Failed to commit to branch '{branch}'"))?;
+
+        Ok(commit_id)
+    }
+
+    /// Merge a branch into the checked-out branch, updating the worktree
+    pub fn merge_branch(&self, branch: &str) -> Result<BranchMergeResult> {
+        let reference = self.repo.find_reference(&format!("refs/heads/{branch}"))
+            .with_context(|| format!("Branch '{branch}' does not exist"))?;
+        let annotated = self.repo.reference_to_annotated_commit(&reference)
+            .context("Failed to prepare branch for merging")?;
+
+        let (analysis, _) = self.repo.merge_analysis(&[&annotated])
+            .context("Failed to analyse merge")?;
+
+        if analysis.is_up_to_date() {
+            return Ok(BranchMergeResult::UpToDate);
+        }
+
+        if analysis.is_fast_forward() || analysis.is_unborn() {
+            let target = self.repo.find_object(annotated.id(), None)
+                .context("Failed to find branch commit")?;
+            self.repo.checkout_tree(&target, Some(CheckoutBuilder::new().safe()))
+                .context("Failed to check out merged files")?;
+            let head_ref = self.repo.find_reference("HEAD")
+                .context("Failed to get HEAD reference")?;
+            let head_target = head_ref.symbolic_target()
+                .ok_or_else(|| anyhow::anyhow!("Cannot merge into a detached HEAD"))?
+                .to_string();
+            self.repo.reference(&head_target, annotated.id(), true, &format!("gcgit: fast-forward to {branch}"))
+                .context("Failed to move branch forward")?;
+            return Ok(BranchMergeResult::FastForward);
+        }
+
+        self.repo.merge(&[&annotated], None, Some(CheckoutBuilder::new().safe()))
+            .with_context(|| format!("Failed to merge '{branch}'"))?;
+
+        let mut index = self.repo.index()
+            .context("Failed to get repository index")?;
+        if index.has_conflicts() {
+            let mut conflicted = Vec::new();
+            for conflict in index.conflicts().context("Failed to read conflicts")? {
+                let conflict = conflict.context("Failed to read conflict")?;
+                let entry = conflict.our.or(conflict.their).or(conflict.ancestor);
+                if let Some(entry) = entry {
+                    conflicted.push(String::from_utf8_lossy(&entry.path).into_owned());
+                }
+            }
+            return Ok(BranchMergeResult::Conflicts(conflicted));
+        }
+
+        let tree_id = index.write_tree()
+            .context("Failed to write merged tree")?;
+        let tree = self.repo.find_tree(tree_id)
+            .context("Failed to find merged tree")?;
+        let head_commit = self.repo.head()
+            .and_then(|head| head.peel_to_commit())
+            .context("Failed to get HEAD commit")?;
+        let branch_commit = self.repo.find_commit(annotated.id())
+            .context("Failed to find branch commit")?;
+        let signature = self.default_signature()?;
+        let target_name = self.current_branch().unwrap_or_else(|| "HEAD".to_string());
+
+        self.create_commit("HEAD", &signature, &signature, &format!("Merge {branch} into {target_name}"), &tree, &[&head_commit, &branch_commit])
+            .context("Failed to create merge commit")?;
+        self.repo.cleanup_state()
+            .context("Failed to clean up merge state")?;
+
+        Ok(BranchMergeResult::Merged)
+    }
+
+    /// List files under a path prefix that differ between a branch and HEAD
+    /// The delta describes the change from the branch to HEAD.
+    pub fn diff_branch_to_head(&self, branch: &str, path_prefix: &str) -> Result<Vec<FileChange>> {
+        let branch_tree = self.repo.find_reference(&format!("refs/heads/{branch}"))
+            .and_then(|reference| reference.peel_to_tree())
+            .with_context(|| format!("Branch '{branch}' does not exist"))?;
+        let head_tree = self.repo.head()
+            .and_then(|head| head.peel_to_tree())
+            .context("Failed to get HEAD tree")?;
+
+        let mut diff_options = DiffOptions::new();
+        if !path_prefix.is_empty() {
+            diff_options.pathspec(path_prefix);
+        }
+        let diff = self.repo.diff_tree_to_tree(Some(&branch_tree), Some(&head_tree), Some(&mut diff_options))
+            .context("Failed to compare branch with HEAD")?;
+
+        let mut changes = Vec::new();
+        for delta in diff.deltas() {
+            let path = delta.new_file().path()
+                .or_else(|| delta.old_file().path())
+                .and_then(|p| p.to_str());
+            if let Some(path) = path {
+                changes.push((path.to_string(), delta.status()));
+            }
+        }
+
+        Ok(changes)
+    }
+
+    /// List files that differ between two revisions, describing the change from the first to the second
+    pub fn diff_revisions(&self, from: &str, to: &str) -> Result<Vec<FileChange>> {
+        let tree_at = |rev: &str| {
+            self.repo.revparse_single(rev)
+                .and_then(|object| object.peel_to_tree())
+                .with_context(|| format!("Revision '{rev}' not found"))
+        };
+        let from_tree = tree_at(from)?;
+        let to_tree = tree_at(to)?;
+
+        let diff = self.repo.diff_tree_to_tree(Some(&from_tree), Some(&to_tree), None)
+            .with_context(|| format!("Failed to compare {from} with {to}"))?;
+
+        let mut changes = Vec::new();
+        for delta in diff.deltas() {
+            let path = delta.new_file().path()
+                .or_else(|| delta.old_file().path())
+                .and_then(|p| p.to_str());
+            if let Some(path) = path {
+                changes.push((path.to_string(), delta.status()));
+            }
+        }
+
+        Ok(changes)
+    }
+
+    /// Create an annotated tag on HEAD; an existing tag is never moved
+    pub fn create_tag(&self, name: &str, message: &str) -> Result<Oid> {
+        if self.reference_exists(&format!("refs/tags/{name}")) {
+            return Err(anyhow::anyhow!("Tag '{name}' already exists"));
+        }
+        let head = self.repo.head()
+            .and_then(|head| head.peel(ObjectType::Commit))
+            .context("Cannot tag an instance without commits")?;
+        let signature = self.default_signature()?;
+
+        self.repo.tag(name, &head, &signature, message, false)
+            .with_context(|| format!("Failed to create tag '{name}'"))
+    }
+
+    /// Count commits HEAD has that the branch lacks, and the reverse
+    pub fn ahead_behind(&self, branch: &str) -> Result<(usize, usize)> {
+        let branch_id = self.repo.find_reference(&format!("refs/heads/{branch}"))
+            .and_then(|reference| reference.peel_to_commit())
+            .with_context(|| format!("Branch '{branch}' does not exist"))?
+            .id();
+        let head_id = self.repo.head()
+            .and_then(|head| head.peel_to_commit())
+            .context("Failed to get HEAD commit")?
+            .id();
+
+        self.repo.graph_ahead_behind(head_id, branch_id)
+            .context("Failed to compare branch history")
+    }
+
+    /// Point a named remote at a URL, creating it or updating its URL as needed
+    pub fn configure_remote(&self, name: &str, url: &str) -> Result<()> {
+        match self.repo.find_remote(name) {
+            Ok(remote) if remote.url() == Some(url) => Ok(()),
+            Ok(_) => self.repo.remote_set_url(name, url)
+                .with_context(|| format!("Failed to update URL of remote '{name}'")),
+            Err(_) => self.repo.remote(name, url)
+                .map(|_| ())
+                .with_context(|| format!("Failed to add remote '{name}'")),
+        }
+    }
+
+    /// Push local branches to a remote, mapping each (local, remote) branch pair
+    /// Pushes are never forced; a branch the remote rejects is reported as an error.
+    pub fn push_branches(&self, remote_name: &str, branches: &[(String, String)], credentials: &RemoteCredentials) -> Result<()> {
+        let refspecs: Vec<String> = branches.iter()
+            .map(|(local, remote_branch)| format!("refs/heads/{local}:refs/heads/{remote_branch}"))
+            .collect();
+        self.push_refs(remote_name, &refspecs, credentials)
+    }
+
+    /// Push refspecs to a remote without forcing
+    pub fn push_refs(&self, remote_name: &str, refspecs: &[String], credentials: &RemoteCredentials) -> Result<()> {
+        let mut remote = self.repo.find_remote(remote_name)
+            .with_context(|| format!("Remote '{remote_name}' is not configured"))?;
+
+        let mut rejected = Vec::new();
+        {
+            let mut callbacks = credential_callbacks(credentials);
+            callbacks.push_update_reference(|refname, status| {
+                if let Some(message) = status {
+                    rejected.push(format!("{refname}: {message}"));
+                }
+                Ok(())
+            });
+
+            let mut push_options = PushOptions::new();
+            push_options.remote_callbacks(callbacks);
+            remote.push(refspecs, Some(&mut push_options))
+                .with_context(|| format!("Failed to push to remote '{remote_name}'"))?;
+        }
+
+        if !rejected.is_empty() {
+            return Err(anyhow::anyhow!("Remote rejected the push:\n  {}", rejected.join("\n  ")));
+        }
+
+        Ok(())
+    }
+
+    /// Fetch one branch from a remote into its remote-tracking reference
+    pub fn fetch_branch(&self, remote_name: &str, branch: &str, credentials: &RemoteCredentials) -> Result<()> {
+        let mut remote = self.repo.find_remote(remote_name)
+            .with_context(|| format!("Remote '{remote_name}' is not configured"))?;
+
+        let mut fetch_options = FetchOptions::new();
+        fetch_options.remote_callbacks(credential_callbacks(credentials));
+        let refspec = format!("+refs/heads/{branch}:refs/remotes/{remote_name}/{branch}");
+        remote.fetch(&[refspec], Some(&mut fetch_options), None)
+            .with_context(|| format!("Failed to fetch {branch} from remote '{remote_name}'"))
+    }
+
+    /// Count commits HEAD has that the remote-tracking branch lacks, and the reverse
+    /// Returns None if the remote branch has never been fetched or pushed.
+    pub fn ahead_behind_remote(&self, remote_name: &str, branch: &str) -> Result<Option<(usize, usize)>> {
+        let Ok(remote_id) = self.repo.find_reference(&format!("refs/remotes/{remote_name}/{branch}"))
+            .and_then(|reference| reference.peel_to_commit())
+            .map(|commit| commit.id()) else {
+            return Ok(None);
+        };
+        let Some(head_id) = self.head_commit_id() else {
+            return Ok(None);
+        };
+
+        self.repo.graph_ahead_behind(head_id, remote_id)
+            .map(Some)
+            .context("Failed to compare with remote history")
+    }
+
+    /// Signature from Git config, falling back to the gcgit identity if not configured
+    fn default_signature(&self) -> Result<Signature<'static>> {
+        match self.repo.signature() {
+            Ok(sig) => Ok(sig.to_owned()),
+            Err(_) => Signature::now("gcgit", "gcgit@localhost")
+                .context("Failed to create fallback signature"),
+        }
+    }
+
     #[allow(dead_code)]
     pub fn get_modified_files_in_instance(&self, instance_name: &str) -> Result<Vec<String>> {
         let statuses = self.get_repository_status()?;
@@ -292,3 +1152,384 @@ impl GitWrapper {
         Ok(modified_files)
     }
 }
+
+/// Remote callbacks that authenticate with the configured credentials
+fn credential_callbacks(credentials: &RemoteCredentials) -> RemoteCallbacks<'_> {
+    let mut attempts = 0;
+    let mut callbacks = RemoteCallbacks::new();
+    callbacks.credentials(move |_url, username_from_url, _allowed| {
+        // libgit2 retries the callback until it succeeds, so give up after a few tries
+        attempts += 1;
+        if attempts > 3 {
+            return Err(git2::Error::from_str("Authentication with the Git remote failed"));
+        }
+        let username = username_from_url.unwrap_or("git");
+        match credentials {
+            RemoteCredentials::SshAgent => Cred::ssh_key_from_agent(username),
+            RemoteCredentials::SshKey { path, passphrase } => Cred::ssh_key(username, None, path, passphrase.as_deref()),
+            RemoteCredentials::Token { username, token } => Cred::userpass_plaintext(username, token),
+        }
+    });
+    callbacks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn commit_file(git: &GitWrapper, dir: &str, path: &str, content: &str, message: &str) {
+        fs::write(format!("{dir}/{path}"), content).unwrap();
+        git.add_files(&[path.to_string()]).unwrap();
+        git.commit(message).unwrap();
+    }
+
+    #[test]
+    fn test_restore_from_revision() {
+        let test_instance = "test_restore_instance";
+        let _ = fs::remove_dir_all(test_instance);
+        fs::create_dir(test_instance).unwrap();
+
+        let git = GitWrapper::new(test_instance).unwrap();
+        commit_file(&git, test_instance, "rule.yaml", "enabled: true\n", "First");
+        commit_file(&git, test_instance, "rule.yaml", "enabled: false\n", "Second");
+        commit_file(&git, test_instance, "extra.yaml", "id: extra\n", "Third");
+
+        let changes = git.restore_from_revision("HEAD~2", &[]).unwrap();
+        assert!(changes.contains(&("rule.yaml".to_string(), RestoreAction::Restored)));
+        assert!(changes.contains(&("extra.yaml".to_string(), RestoreAction::Removed)));
+
+        assert_eq!(fs::read_to_string(format!("{test_instance}/rule.yaml")).unwrap(), "enabled: true\n");
+        assert!(!std::path::Path::new(&format!("{test_instance}/extra.yaml")).exists());
+
+        // Restoring again is a no-op
+        assert!(git.preview_restore("HEAD~2", &[]).unwrap().is_empty());
+
+        let _ = fs::remove_dir_all(test_instance);
+    }
+
+    #[test]
+    fn test_restore_limited_to_paths() {
+        let test_instance = "test_restore_paths_instance";
+        let _ = fs::remove_dir_all(test_instance);
+        fs::create_dir(test_instance).unwrap();
+
+        let git = GitWrapper::new(test_instance).unwrap();
+        commit_file(&git, test_instance, "a.yaml", "v: 1\n", "First");
+        commit_file(&git, test_instance, "b.yaml", "v: 1\n", "Second");
+        commit_file(&git, test_instance, "a.yaml", "v: 2\n", "Third");
+        commit_file(&git, test_instance, "b.yaml", "v: 2\n", "Fourth");
+
+        let changes = git.restore_from_revision("HEAD~2", &["a.yaml".to_string()]).unwrap();
+        assert_eq!(changes, vec![("a.yaml".to_string(), RestoreAction::Restored)]);
+        assert_eq!(fs::read_to_string(format!("{test_instance}/a.yaml")).unwrap(), "v: 1\n");
+        assert_eq!(fs::read_to_string(format!("{test_instance}/b.yaml")).unwrap(), "v: 2\n");
+
+        let _ = fs::remove_dir_all(test_instance);
+    }
+
+    #[test]
+    fn test_locally_modified_files_and_history() {
+        let test_instance = "test_local_edits_instance";
+        let _ = fs::remove_dir_all(test_instance);
+        fs::create_dir_all(format!("{test_instance}/xsiam")).unwrap();
+        fs::create_dir_all(format!("{test_instance}/appsec")).unwrap();
+        fs::create_dir_all(format!("{test_instance}/xsiam/.metadata")).unwrap();
+
+        let git = GitWrapper::new(test_instance).unwrap();
+        commit_file(&git, test_instance, "xsiam/rule.yaml", "v: 1\n", "First");
+        commit_file(&git, test_instance, "appsec/app.yaml", "v: 1\n", "Second");
+        commit_file(&git, test_instance, "xsiam/.metadata/biocs.yaml", "v: 1\n", "Third");
+
+        fs::write(format!("{test_instance}/xsiam/rule.yaml"), "v: 2\n").unwrap();
+        fs::write(format!("{test_instance}/appsec/app.yaml"), "v: 2\n").unwrap();
+        fs::write(format!("{test_instance}/xsiam/untracked.yaml"), "v: 1\n").unwrap();
+        // Metadata index changes are platform state, not local edits
+        fs::write(format!("{test_instance}/xsiam/.metadata/biocs.yaml"), "v: 2\n").unwrap();
+
+        assert_eq!(git.get_locally_modified_files("xsiam").unwrap(), vec!["xsiam/rule.yaml".to_string()]);
+        assert_eq!(git.read_file_at_revision("HEAD", "xsiam/rule.yaml").unwrap(), Some("v: 1\n".to_string()));
+        assert_eq!(git.read_file_at_revision("HEAD", "xsiam/missing.yaml").unwrap(), None);
+
+        let _ = fs::remove_dir_all(test_instance);
+    }
+
+    #[test]
+    fn test_unstage_keeps_worktree_content() {
+        let test_instance = "test_unstage_instance";
+        let _ = fs::remove_dir_all(test_instance);
+        fs::create_dir(test_instance).unwrap();
+
+        let git = GitWrapper::new(test_instance).unwrap();
+        commit_file(&git, test_instance, "index.yaml", "v: 1\n", "First");
+        fs::write(format!("{test_instance}/index.yaml"), "v: 2\n").unwrap();
+        fs::write(format!("{test_instance}/new.yaml"), "v: 1\n").unwrap();
+        git.add_files(&["index.yaml".to_string(), "new.yaml".to_string()]).unwrap();
+
+        git.unstage_paths(&["index.yaml".to_string(), "new.yaml".to_string()]).unwrap();
+        let status: std::collections::HashMap<String, Status> = git.get_repository_status().unwrap().into_iter().collect();
+        assert_eq!(status["index.yaml"], Status::WT_MODIFIED);
+        assert_eq!(status["new.yaml"], Status::WT_NEW);
+        assert_eq!(fs::read_to_string(format!("{test_instance}/index.yaml")).unwrap(), "v: 2\n");
+
+        let _ = fs::remove_dir_all(test_instance);
+    }
+
+    #[test]
+    fn test_stash_limited_to_module() {
+        let test_instance = "test_stash_module_instance";
+        let _ = fs::remove_dir_all(test_instance);
+        fs::create_dir_all(format!("{test_instance}/xsiam")).unwrap();
+        fs::create_dir_all(format!("{test_instance}/appsec")).unwrap();
+
+        let mut git = GitWrapper::new(test_instance).unwrap();
+        commit_file(&git, test_instance, "xsiam/rule.yaml", "v: 1\n", "First");
+        commit_file(&git, test_instance, "appsec/app.yaml", "v: 1\n", "Second");
+        fs::write(format!("{test_instance}/xsiam/rule.yaml"), "v: 2\n").unwrap();
+        fs::write(format!("{test_instance}/appsec/app.yaml"), "v: 2\n").unwrap();
+
+        let module_files = git.get_locally_modified_files("xsiam").unwrap();
+        assert!(git.stash_local_changes(&module_files, "before pull").unwrap());
+        let stashed_rule = fs::read_to_string(format!("{test_instance}/xsiam/rule.yaml")).unwrap();
+        let other_module = fs::read_to_string(format!("{test_instance}/appsec/app.yaml")).unwrap();
+        git.pop_stashed_changes().unwrap();
+        let popped_rule = fs::read_to_string(format!("{test_instance}/xsiam/rule.yaml")).unwrap();
+        let _ = fs::remove_dir_all(test_instance);
+
+        assert_eq!(stashed_rule, "v: 1\n");
+        assert_eq!(other_module, "v: 2\n");
+        assert_eq!(popped_rule, "v: 2\n");
+    }
+
+    #[test]
+    fn test_local_edits_backup_round_trip() {
+        let test_instance = "test_local_edits_backup_instance";
+        let _ = fs::remove_dir_all(test_instance);
+        fs::create_dir(test_instance).unwrap();
+
+        let git = GitWrapper::new(test_instance).unwrap();
+        let edits: Vec<LocalEdit> = vec![
+            ("xsiam/biocs/Rule.yaml".to_string(), Some("v: 1\n".to_string()), Some("v: 2\n".to_string())),
+            ("xsiam/biocs/New.yaml".to_string(), None, Some("v: 1\n".to_string())),
+        ];
+        git.write_local_edits_backup("xsiam", &edits).unwrap();
+        let read_back = git.read_local_edits_backup("xsiam").unwrap();
+        let in_git_dir = git.local_edits_backup_path("xsiam").starts_with(git.repo.path());
+        git.remove_local_edits_backup("xsiam").unwrap();
+        let after_removal = git.read_local_edits_backup("xsiam").unwrap();
+        let _ = fs::remove_dir_all(test_instance);
+
+        assert_eq!(read_back, Some(edits));
+        assert!(in_git_dir);
+        assert_eq!(after_removal, None);
+    }
+
+    #[test]
+    fn test_platform_branch_commit_and_merge() {
+        let test_instance = "test_platform_branch_instance";
+        let _ = fs::remove_dir_all(test_instance);
+        fs::create_dir_all(format!("{test_instance}/xsiam")).unwrap();
+
+        let git = GitWrapper::new(test_instance).unwrap();
+        commit_file(&git, test_instance, "xsiam/rule.yaml", "v: 1\n", "First");
+
+        let branch = format!("{PLATFORM_BRANCH_PREFIX}xsiam");
+        git.create_branch(&branch, git.head_commit_id().unwrap()).unwrap();
+        let files = vec![
+            ("xsiam/rule.yaml".to_string(), b"v: 1\n".to_vec()),
+            ("xsiam/new.yaml".to_string(), b"v: 1\n".to_vec()),
+        ];
+        assert_eq!(git.changed_files_on_branch(&branch, &files).unwrap(), vec![("xsiam/new.yaml".to_string(), Delta::Added)]);
+
+        git.commit_files_to_branch(&branch, &files, &[], "Pull", None).unwrap();
+        assert!(git.branch_exists(&branch));
+        assert!(git.changed_files_on_branch(&branch, &files).unwrap().is_empty());
+        // The worktree is untouched until the branch is merged
+        assert!(!std::path::Path::new(&format!("{test_instance}/xsiam/new.yaml")).exists());
+        assert_eq!(git.ahead_behind(&branch).unwrap(), (0, 1));
+
+        assert_eq!(git.merge_branch(&branch).unwrap(), BranchMergeResult::FastForward);
+        assert!(std::path::Path::new(&format!("{test_instance}/xsiam/new.yaml")).exists());
+        assert_eq!(git.merge_branch(&branch).unwrap(), BranchMergeResult::UpToDate);
+
+        // Local work on the checked-out branch shows up against the platform branch
+        commit_file(&git, test_instance, "xsiam/local.yaml", "v: 1\n", "Local");
+        let changes = git.diff_branch_to_head(&branch, "xsiam").unwrap();
+        assert_eq!(changes, vec![("xsiam/local.yaml".to_string(), Delta::Added)]);
+
+        // Objects deleted on the platform are removed from the branch
+        let mirrored = ["xsiam".to_string()];
+        assert_eq!(git.branch_files_under(&branch, &mirrored).unwrap(), vec!["xsiam/new.yaml".to_string(), "xsiam/rule.yaml".to_string()]);
+        git.commit_files_to_branch(&branch, &[], &["xsiam/new.yaml".to_string()], "Pull", None).unwrap();
+        assert_eq!(git.branch_files_under(&branch, &mirrored).unwrap(), vec!["xsiam/rule.yaml".to_string()]);
+
+        let _ = fs::remove_dir_all(test_instance);
+    }
+
+    #[test]
+    fn test_missing_platform_branch_does_not_start_from_head() {
+        let test_instance = "test_platform_branch_root_instance";
+        let _ = fs::remove_dir_all(test_instance);
+        fs::create_dir_all(format!("{test_instance}/xsiam")).unwrap();
+
+        let git = GitWrapper::new(test_instance).unwrap();
+        commit_file(&git, test_instance, "xsiam/rule.yaml", "v: 1\n", "Human edit");
+        commit_file(&git, test_instance, "notes.md", "local\n", "Local notes");
+
+        let branch = format!("{PLATFORM_BRANCH_PREFIX}xsiam");
+        let files = vec![("xsiam/rule.yaml".to_string(), b"v: 2\n".to_vec())];
+        assert_eq!(git.changed_files_on_branch(&branch, &files).unwrap(), vec![("xsiam/rule.yaml".to_string(), Delta::Added)]);
+        assert!(git.branch_files_under(&branch, &["xsiam".to_string()]).unwrap().is_empty());
+
+        let commit_id = git.commit_files_to_branch(&branch, &files, &[], "Pull", None).unwrap();
+        let commit = git.repo.find_commit(commit_id).unwrap();
+        let tree = commit.tree().unwrap();
+
+        // Only platform state, with none of the human or local commits behind it
+        assert_eq!(commit.parent_count(), 0);
+        assert!(tree.get_path(std::path::Path::new("notes.md")).is_err());
+        assert_eq!(tree.len(), 1);
+
+        // Merging it shows where platform and local state disagree
+        let merged = git.merge_branch(&branch).unwrap();
+        let _ = fs::remove_dir_all(test_instance);
+        assert_eq!(merged, BranchMergeResult::Conflicts(vec!["xsiam/rule.yaml".to_string()]));
+    }
+
+    #[test]
+    fn test_push_branches_to_bare_remote() {
+        let test_instance = "test_push_instance";
+        let bare_path = "test_push_remote.git";
+        let _ = fs::remove_dir_all(test_instance);
+        let _ = fs::remove_dir_all(bare_path);
+        fs::create_dir(test_instance).unwrap();
+        let bare = Repository::init_bare(bare_path).unwrap();
+
+        let git = GitWrapper::new(test_instance).unwrap();
+        commit_file(&git, test_instance, "rule.yaml", "v: 1\n", "First");
+        let branch = git.current_branch().unwrap();
+
+        let url = std::fs::canonicalize(bare_path).unwrap().to_string_lossy().into_owned();
+        git.configure_remote("origin", &url).unwrap();
+        // Configuring the same URL again is a no-op
+        git.configure_remote("origin", &url).unwrap();
+
+        let branches = vec![(branch.clone(), "main".to_string())];
+        git.push_branches("origin", &branches, &RemoteCredentials::SshAgent).unwrap();
+        let head_id = git.repo.head().unwrap().peel_to_commit().unwrap().id();
+        assert_eq!(bare.find_reference("refs/heads/main").unwrap().target(), Some(head_id));
+
+        // Status compares HEAD with the fetched remote branch
+        assert_eq!(git.ahead_behind_remote("origin", "develop").unwrap(), None);
+        git.fetch_branch("origin", "main", &RemoteCredentials::SshAgent).unwrap();
+        assert_eq!(git.ahead_behind_remote("origin", "main").unwrap(), Some((0, 0)));
+        commit_file(&git, test_instance, "rule.yaml", "v: 2\n", "Second");
+        assert_eq!(git.ahead_behind_remote("origin", "main").unwrap(), Some((1, 0)));
+
+        // A diverged local history is rejected rather than overwriting the remote
+        let local_repo = &git.repo;
+        let tree = local_repo.head().unwrap().peel_to_tree().unwrap();
+        let signature = git.default_signature().unwrap();
+        let orphan = local_repo.commit(None, &signature, &signature, "Orphan", &tree, &[]).unwrap();
+        local_repo.reference(&format!("refs/heads/{branch}"), orphan, true, "test").unwrap();
+        assert!(git.push_branches("origin", &branches, &RemoteCredentials::SshAgent).is_err());
+
+        let _ = fs::remove_dir_all(test_instance);
+        let _ = fs::remove_dir_all(bare_path);
+    }
+
+    #[test]
+    fn test_commit_staged_paths_per_author() {
+        let test_instance = "test_author_commits_instance";
+        let _ = fs::remove_dir_all(test_instance);
+        fs::create_dir(test_instance).unwrap();
+
+        let git = GitWrapper::new(test_instance).unwrap();
+        commit_file(&git, test_instance, "a.yaml", "v: 1\n", "First");
+
+        fs::write(format!("{test_instance}/a.yaml"), "v: 2\n").unwrap();
+        fs::write(format!("{test_instance}/b.yaml"), "v: 1\n").unwrap();
+        let (_, count, _) = git.has_changes_after_add(&["a.yaml".to_string(), "b.yaml".to_string()], &[]).unwrap();
+        assert_eq!(count, 2);
+
+        let author = CommitAuthor { name: "Jane Doe".to_string(), email: "jane@example.com".to_string() };
+        git.commit_staged_paths(&["a.yaml".to_string()], "Jane's change", Some(&author)).unwrap();
+
+        let head = git.repo.head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(head.author().email(), Some("jane@example.com"));
+        assert_ne!(head.committer().email(), Some("jane@example.com"));
+        assert!(head.tree().unwrap().get_path(std::path::Path::new("b.yaml")).is_err());
+
+        // The remaining staged file is committed separately
+        let (_, remaining, _) = git.has_changes_after_add(&[], &[]).unwrap();
+        assert_eq!(remaining, 1);
+        git.commit_staged_paths(&["b.yaml".to_string()], "Unattributed", None).unwrap();
+        let (_, remaining, _) = git.has_changes_after_add(&[], &[]).unwrap();
+        assert_eq!(remaining, 0);
+
+        let _ = fs::remove_dir_all(test_instance);
+    }
+
+    #[test]
+    fn test_removed_files_are_staged_as_deletions() {
+        let test_instance = "test_removed_files_instance";
+        let _ = fs::remove_dir_all(test_instance);
+        fs::create_dir_all(format!("{test_instance}/xsiam/biocs")).unwrap();
+
+        let git = GitWrapper::new(test_instance).unwrap();
+        commit_file(&git, test_instance, "xsiam/biocs/Kept.yaml", "v: 1\n", "First");
+        commit_file(&git, test_instance, "xsiam/biocs/Gone.yaml", "v: 1\n", "Second");
+        let mut tracked = git.tracked_files_under(&["xsiam/biocs".to_string()]).unwrap();
+        tracked.sort();
+
+        fs::remove_file(format!("{test_instance}/xsiam/biocs/Gone.yaml")).unwrap();
+        let (_, _, changes) = git.has_changes_after_add(&["xsiam/biocs/Kept.yaml".to_string()], &["xsiam/biocs/Gone.yaml".to_string()]).unwrap();
+        let _ = fs::remove_dir_all(test_instance);
+
+        assert_eq!(tracked, vec!["xsiam/biocs/Gone.yaml".to_string(), "xsiam/biocs/Kept.yaml".to_string()]);
+        assert_eq!(changes, vec![("xsiam/biocs/Gone.yaml".to_string(), Delta::Deleted)]);
+    }
+
+    #[test]
+    fn test_find_latest_commit_and_notes() {
+        let test_instance = "test_notes_instance";
+        let _ = fs::remove_dir_all(test_instance);
+        fs::create_dir(test_instance).unwrap();
+
+        let git = GitWrapper::new(test_instance).unwrap();
+        assert!(git.find_latest_commit("HEAD", |_| true).unwrap().is_none());
+
+        commit_file(&git, test_instance, "a.yaml", "v: 1\n", "Pull\n\nGcgit-Module: xsiam\n");
+        commit_file(&git, test_instance, "a.yaml", "v: 2\n", "Local edit");
+
+        let (pull_id, _) = git.find_latest_commit("HEAD", |message| message.contains("Gcgit-Module: xsiam")).unwrap().unwrap();
+        assert_ne!(Some(pull_id), git.repo.head().unwrap().target());
+
+        git.add_note("refs/notes/test", pull_id, "audit entries").unwrap();
+        assert_eq!(git.read_note("refs/notes/test", pull_id), Some("audit entries".to_string()));
+        assert!(git.reference_exists("refs/notes/test"));
+
+        let _ = fs::remove_dir_all(test_instance);
+    }
+
+    #[test]
+    fn test_tags_and_revision_diff() {
+        let test_instance = "test_tags_instance";
+        let _ = fs::remove_dir_all(test_instance);
+        fs::create_dir(test_instance).unwrap();
+
+        let git = GitWrapper::new(test_instance).unwrap();
+        commit_file(&git, test_instance, "a.yaml", "v: 1\n", "First");
+        git.create_tag("q1", "Quarter one").unwrap();
+        assert!(git.create_tag("q1", "Again").is_err());
+
+        commit_file(&git, test_instance, "a.yaml", "v: 2\n", "Second");
+        commit_file(&git, test_instance, "b.yaml", "v: 1\n", "Third");
+        git.create_tag("q2", "Quarter two").unwrap();
+
+        let changes = git.diff_revisions("q1", "q2").unwrap();
+        assert_eq!(changes, vec![("a.yaml".to_string(), Delta::Modified), ("b.yaml".to_string(), Delta::Added)]);
+
+        let _ = fs::remove_dir_all(test_instance);
+    }
+}
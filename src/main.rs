@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
 use clap::{Parser, CommandFactory};
-use anyhow::Result;
+use anyhow::{Context, Result};
 
 mod cli;
 mod config;
@@ -14,12 +14,30 @@ mod types;
 mod zip_safety;
 mod modules;
 mod lock;
+mod merge;
+mod signing;
+mod commit_message;
+mod audit;
+mod object_diff;
+mod report;
+mod compare;
+mod promote;
+mod templating;
+mod baseline;
+mod fleet;
+mod status;
+mod sidecar;
+mod script_code;
+mod block_scalars;
+mod normalise;
+mod metadata_index;
+mod archive;
 
-use cli::{Cli, Commands, ModuleCommands};
+use cli::{BaselineCommands, Cli, Commands, LocalChangesMode, ModuleCommands, PlatformCommands, ReportFormat};
 use config::ConfigManager;
 use git_wrapper::GitWrapper;
 use parser::YamlParser;
-use modules::ModuleRegistry;
+use modules::{Module, ModuleRegistry};
 use lock::InstanceLock;
 
 #[tokio::main]
@@ -36,7 +54,10 @@ async fn main() -> Result<()> {
         Some(Commands::Init { instance }) => {
             handle_init_command(instance).await?;
         }
-        Some(Commands::Status { instance }) => {
+        Some(Commands::Status { instance: _, group: Some(group) }) => {
+            handle_group_command(&group)?;
+        }
+        Some(Commands::Status { instance, group: None }) => {
             handle_status_command(instance).await?;
         }
         Some(Commands::Deploy { instance: _, message: _, files: _ }) => {
@@ -51,6 +72,33 @@ async fn main() -> Result<()> {
         Some(Commands::Validate { instance, files }) => {
             handle_validate_command(instance, files).await?;
         }
+        Some(Commands::Restore { instance, rev, push, paths }) => {
+            handle_restore_command(instance, rev, push, paths).await?;
+        }
+        Some(Commands::Snapshot { instance, tag, message }) => {
+            handle_snapshot_command(instance, tag, message)?;
+        }
+        Some(Commands::Report { instance, from, to, format, output }) => {
+            handle_report_command(instance, from, to, format, output)?;
+        }
+        Some(Commands::Compare { left, right, module }) => {
+            handle_compare_command(left, right, module)?;
+        }
+        Some(Commands::Promote { from, to, content_type, select }) => {
+            handle_promote_command(from, to, content_type, select)?;
+        }
+        Some(Commands::Verify { instance, rev }) => {
+            handle_verify_command(instance, rev)?;
+        }
+        Some(Commands::Sync { instance }) => {
+            push_instance_repo(&instance.unwrap_or_else(|| "default".to_string()))?;
+        }
+        Some(Commands::Platform { command }) => {
+            handle_platform_command(command).await?;
+        }
+        Some(Commands::Baseline { command: BaselineCommands::Check { instance, baseline, module, fail_on } }) => {
+            handle_baseline_check_command(instance, baseline, module, fail_on)?;
+        }
         None => {
             // No command provided, show help with version (same as --help)
             let mut cmd = Cli::command();
@@ -69,6 +117,11 @@ async fn handle_module_command(module_id: &str, command: ModuleCommands) -> Resu
         .ok_or_else(|| anyhow::anyhow!("Module '{module_id}' not found"))?;
     
     match command {
+        ModuleCommands::Pull { group: Some(group), .. }
+        | ModuleCommands::Diff { group: Some(group), .. }
+        | ModuleCommands::Test { group: Some(group), .. } => {
+            handle_group_command(&group)?;
+        }
         ModuleCommands::Push { instance: _ } => {
             let module_upper = module_id.to_uppercase();
             eprintln!("ERROR: Feature not yet available");
@@ -79,132 +132,14 @@ async fn handle_module_command(module_id: &str, command: ModuleCommands) -> Resu
             eprintln!("Visit https://gocortex.io for updates on feature availability.");
             std::process::exit(1);
         }
-        ModuleCommands::Pull { instance } => {
+        ModuleCommands::Pull { instance, group: _, local_changes, git_push, attribute_authors, audit_log } => {
             let instance_name = instance.unwrap_or_else(|| "default".to_string());
-            
-            // Acquire lock to prevent concurrent operations on the same instance
-            let _lock = InstanceLock::acquire(&instance_name)?;
-            
-            let config_manager = ConfigManager::new();
-            let module_config = config_manager.load_module_config(&instance_name, module_id)?;
-            
-            // Check if module is enabled
-            if !module_config.enabled {
-                println!("Module '{module_id}' is disabled in instance '{instance_name}'. Enable it in config.toml to use this command.");
-                return Ok(());
-            }
-            
-            let module_client = api::ModuleClient::new(module_config, module.base_api_path());
-            let yaml_parser = YamlParser::new();
-            
-            // Pull each content type defined in the module
-            let content_types = module.content_types();
-            
-            let mut _total_pulled = 0;
-            let mut pulled_files = Vec::new();
-            
-            for content_def in content_types {
-                println!("Pulling {}...", content_def.name);
-                match module_client.pull_content_type(&content_def).await {
-                    Ok(objects) => {
-                        println!("  Found {} {}(s)", objects.len(), content_def.name);
-
-                        // Build base filenames and detect collisions
-                        let base_names: Vec<String> = objects.iter().map(|obj| {
-                            if let Some(name) = &obj.name {
-                                if name.trim().is_empty() {
-                                    format!("{}_id_{}", content_def.name.trim_end_matches('s'), obj.id)
-                                } else {
-                                    name.replace([' ', '/', '\\'], "_")
-                                }
-                            } else {
-                                format!("{}_id_{}", content_def.name.trim_end_matches('s'), obj.id)
-                            }
-                        }).collect();
-
-                        // Count occurrences of each base name
-                        let mut name_counts: std::collections::HashMap<&str, usize> = std::collections::HashMap::new();
-                        for name in &base_names {
-                            *name_counts.entry(name.as_str()).or_insert(0) += 1;
-                        }
-
-                        for (object, base_name) in objects.iter().zip(base_names.iter()) {
-                            // Disambiguate colliding names by appending the object ID
-                            let filename = if name_counts.get(base_name.as_str()).copied().unwrap_or(1) > 1 {
-                                format!("{}_{}", base_name, object.id)
-                            } else {
-                                base_name.clone()
-                            };
-
-                            let file_path = format!("{}/{}/{}/{}.yaml", instance_name, module_id, content_def.name, filename);
-                            yaml_parser.write_file(&file_path, object)?;
-                            println!("  Pulled: {file_path}");
-                            let relative_path = format!("{}/{}/{}.yaml", module_id, content_def.name, filename);
-                            pulled_files.push(relative_path);
-                            _total_pulled += 1;
-                        }
-                    }
-                    Err(e) => {
-                        println!("  WARNING: Failed to pull {} - {}", content_def.name, e);
-                        println!("  (This endpoint may not be available on your instance)");
-                    }
-                }
-            }
-            
-            // Auto-commit pulled changes using Git's native change detection
-            if !pulled_files.is_empty() {
-                println!("\nProcessing pulled files for Git repository...");
-                
-                match GitWrapper::new_for_instance(&instance_name) {
-                    Ok(git_wrapper) => {
-                        // Use Git's native change detection - much faster than API calls
-                        match git_wrapper.has_changes_after_add(&pulled_files) {
-                            Ok((true, changed_count, changed_files)) => {
-                                // Create descriptive commit message with changed files
-                                let changed_file_names: Vec<String> = changed_files.iter()
-                                    .map(|path| {
-                                        // Extract just the filename from the path for readability
-                                        if let Some(filename) = path.split('/').next_back() {
-                                            filename.replace(".yaml", "")
-                                        } else {
-                                            path.clone()
-                                        }
-                                    })
-                                    .collect();
-                                
-                                let module_upper = module_id.to_uppercase();
-                                let commit_message = if changed_count == 1 {
-                                    format!("Auto-commit: Updated {} from {}", changed_file_names[0], module_upper)
-                                } else if changed_count <= 3 {
-                                    format!("Auto-commit: Updated {} from {}", changed_file_names.join(", "), module_upper)
-                                } else {
-                                    format!("Auto-commit: Updated {} files from {} ({})", changed_count, module_upper, changed_file_names[..2].join(", "))
-                                };
-                                
-                                if let Err(e) = git_wrapper.commit(&commit_message) {
-                                    println!("Warning: Failed to commit changes: {e}");
-                                } else {
-                                    let file_word = if changed_count == 1 { "file" } else { "files" };
-                                    println!("Successfully processed {} pulled files to instance Git repository", pulled_files.len());
-                                    println!("  {changed_count} {file_word} actually changed and committed");
-                                }
-                            }
-                            Ok((false, _, _)) => {
-                                println!("Successfully processed {} pulled files to instance Git repository", pulled_files.len());
-                                println!("  No Git changes detected - objects serialise to identical YAML");
-                            }
-                            Err(e) => {
-                                println!("Warning: Failed to check for changes: {e}");
-                            }
-                        }
-                    }
-                    Err(e) => {
-                        println!("Warning: Failed to initialise Git repository for instance: {e}");
-                    }
-                }
+            handle_pull_command(module, module_id, Some(instance_name.clone()), local_changes, attribute_authors, audit_log).await?;
+            if git_push {
+                push_instance_repo(&instance_name)?;
             }
         }
-        ModuleCommands::Diff { instance } => {
+        ModuleCommands::Diff { instance, group: _ } => {
             let instance_name = instance.unwrap_or_else(|| "default".to_string());
             
             let config_manager = ConfigManager::new();
@@ -218,6 +153,8 @@ async fn handle_module_command(module_id: &str, command: ModuleCommands) -> Resu
             
             let module_client = api::ModuleClient::new(module_config, module.base_api_path());
             let yaml_parser = YamlParser::new();
+            let templating = config_manager.load_templating(&instance_name)?;
+            let normaliser = config_manager.load_normaliser(&instance_name)?;
             
             // Get local files from the module-specific directory
             let module_dir = format!("{instance_name}/{module_id}");
@@ -242,7 +179,9 @@ async fn handle_module_command(module_id: &str, command: ModuleCommands) -> Resu
             let content_types = module.content_types();
             
             for file_path in local_files {
-                let local_content = yaml_parser.parse_file(&file_path)?;
+                // Compare the rendered payload, with overlays and variables applied
+                let relative_path = file_path.strip_prefix(&format!("{instance_name}/")).unwrap_or(&file_path);
+                let local_content = normaliser.apply(&templating.render(&yaml_parser.parse_file(&file_path)?, relative_path)?);
                 
                 // Find the ContentTypeDefinition for this content type
                 let content_def = content_types
@@ -252,6 +191,8 @@ async fn handle_module_command(module_id: &str, command: ModuleCommands) -> Resu
                 
                 match module_client.get_object_by_id(content_def, &local_content.id).await {
                     Ok(remote_content) => {
+                        // Volatile fields are normalised on both sides so they never show as differences
+                        let remote_content = normaliser.apply(&remote_content);
                         // Use logical comparison (excludes metadata for accurate functional comparison)
                         match yaml_parser.objects_are_logically_equal(&local_content, &remote_content) {
                             Ok(are_equal) => {
@@ -285,7 +226,7 @@ async fn handle_module_command(module_id: &str, command: ModuleCommands) -> Resu
                 println!("No differences detected - local YAML files match remote {} objects", module_id.to_uppercase());
             }
         }
-        ModuleCommands::Test { instance } => {
+        ModuleCommands::Test { instance, group: _ } => {
             let instance_name = instance.unwrap_or_else(|| "default".to_string());
             
             let config_manager = ConfigManager::new();
@@ -366,6 +307,14 @@ async fn handle_module_command(module_id: &str, command: ModuleCommands) -> Resu
                 }
             }
         }
+        ModuleCommands::VerifyCode { instance, remote } => {
+            let instance_name = instance.unwrap_or_else(|| "default".to_string());
+            handle_verify_code_command(module, &instance_name, remote).await?;
+        }
+        ModuleCommands::Rederive { instance, archive, content_type } => {
+            let instance_name = instance.unwrap_or_else(|| "default".to_string());
+            handle_rederive_command(module, &instance_name, archive, content_type)?;
+        }
         ModuleCommands::Delete { instance: _, content_type: _, id: _ } => {
             let module_upper = module_id.to_uppercase();
             eprintln!("ERROR: Feature not yet available");
@@ -381,166 +330,1683 @@ async fn handle_module_command(module_id: &str, command: ModuleCommands) -> Resu
     Ok(())
 }
 
-async fn handle_init_command(instance: String) -> Result<()> {
-    let config_manager = ConfigManager::new();
-    config_manager.init_instance(&instance)?;
-    
-    println!("Initialised instance: {instance}");
-    println!("Please edit {instance}/config.toml with your API credentials");
-    println!("  Configure modules.xsiam for XSIAM platform access");
-    println!("  Configure modules.appsec for Application Security platform access");
-    
-    Ok(())
-}
-
-async fn handle_status_command(instance: Option<String>) -> Result<()> {
-    let config_manager = ConfigManager::new();
-    
-    match instance {
-        Some(instance_name) => {
-            println!("Status for instance: {instance_name}");
-            show_instance_status(&config_manager, &instance_name).await?;
-        }
-        None => {
-            println!("Status for all instances:");
-            // Get all instance directories
-            let instances = get_all_instances()?;
-            for instance_name in instances {
-                println!("\n=== {instance_name} ===");
-                show_instance_status(&config_manager, &instance_name).await?;
-            }
-        }
+/// Check local script code against the SHA-256 recorded at pull time, and optionally the live platform code
+async fn handle_verify_code_command(module: &dyn Module, instance_name: &str, remote: bool) -> Result<()> {
+    let module_id = module.id();
+    let content_types: Vec<_> = module.content_types().into_iter()
+        .filter(|ct| matches!(ct.pull_strategy, modules::PullStrategy::ScriptCode { .. }))
+        .collect();
+    if content_types.is_empty() {
+        println!("Module '{module_id}' has no script content types");
+        return Ok(());
     }
-    
-    Ok(())
-}
 
-async fn handle_validate_command(instance: Option<String>, files: Vec<String>) -> Result<()> {
-    let yaml_parser = YamlParser::new();
-    let module_registry = ModuleRegistry::load();
-    
-    // Collect all content type names from all modules for validation
-    let all_content_types: Vec<&str> = module_registry.all_modules()
-        .iter()
-        .flat_map(|module| module.content_types())
-        .map(|ct| ct.name)
-        .collect();
-    
-    // Determine files to validate
-    let files_to_validate = if !files.is_empty() {
-        files
-    } else if let Some(instance_name) = &instance {
-        // Get all YAML files in the specified instance across all modules
-        let mut instance_files = Vec::new();
-        for module in module_registry.all_modules() {
-            let module_dir = format!("{}/{}", instance_name, module.id());
-            if let Ok(files) = yaml_parser.get_local_files(&module_dir, &all_content_types) {
-                instance_files.extend(files);
-            }
-        }
-        instance_files
+    let module_client = if remote {
+        let module_config = ConfigManager::new().load_module_config(instance_name, module_id)?;
+        Some(api::ModuleClient::new(module_config, module.base_api_path()))
     } else {
-        // Get all YAML files in all instances
-        let instances = get_all_instances()?;
-        let mut all_files = Vec::new();
-        for inst in instances {
-            for module in module_registry.all_modules() {
-                let module_dir = format!("{}/{}", inst, module.id());
-                if let Ok(files) = yaml_parser.get_local_files(&module_dir, &all_content_types) {
-                    all_files.extend(files);
-                }
-            }
-        }
-        all_files
+        None
     };
-    
-    if files_to_validate.is_empty() {
-        println!("No YAML files found to validate");
-        return Ok(());
-    }
-    
-    println!("Validating {} files...", files_to_validate.len());
-    let mut validation_errors = 0;
-    
-    for file_path in files_to_validate {
-        print!("  Checking {file_path}... ");
-        
-        match yaml_parser.parse_file(&file_path) {
-            Ok(xsiam_object) => {
-                // Validate content type is supported by checking against all registered modules
-                if all_content_types.contains(&xsiam_object.content_type.as_str()) {
-                    println!("Valid");
-                } else {
-                    println!("INVALID: Unsupported content type: {}", xsiam_object.content_type);
-                    validation_errors += 1;
+
+    let yaml_parser = YamlParser::new();
+    let module_dir = format!("{instance_name}/{module_id}");
+    let (mut verified, mut failed, mut unhashed) = (0, 0, 0);
+
+    for content_def in &content_types {
+        for file_path in yaml_parser.get_local_files(&module_dir, &[content_def.name])? {
+            let object = yaml_parser.parse_file(&file_path)?;
+            match script_code::check(&object) {
+                script_code::CodeCheck::Match => {}
+                script_code::CodeCheck::Mismatch { expected, actual } => {
+                    failed += 1;
+                    println!("  MISMATCH: {file_path} (pulled {expected}, local {actual})");
+                    continue;
                 }
+                script_code::CodeCheck::NoHash => {
+                    unhashed += 1;
+                    println!("  NO HASH: {file_path} (pull again to record the platform hash)");
+                    continue;
+                }
+                script_code::CodeCheck::NoCode => continue,
             }
-            Err(e) => {
-                println!("ERROR: {e}");
-                validation_errors += 1;
+
+            if let Some(module_client) = &module_client {
+                let local_code = object.content.get("code").and_then(|code| code.as_str()).unwrap_or_default();
+                match module_client.fetch_script_code(content_def, &object.id).await {
+                    Ok(platform_code) if platform_code.as_bytes() == local_code.as_bytes() => {}
+                    Ok(platform_code) => {
+                        failed += 1;
+                        println!("  CHANGED ON PLATFORM: {file_path} (platform {})", script_code::sha256_hex(platform_code.as_bytes()));
+                        continue;
+                    }
+                    Err(e) => {
+                        failed += 1;
+                        println!("  FAILED: {file_path} - {e}");
+                        continue;
+                    }
+                }
             }
+            verified += 1;
         }
     }
-    
-    if validation_errors > 0 {
-        println!("\n{validation_errors} validation errors found");
-        return Err(anyhow::anyhow!("Validation failed"));
-    } else {
-        println!("\nAll files are valid");
+
+    let against = if remote { "the platform" } else { "the pulled hashes" };
+    println!("{verified} script(s) match {against} byte for byte, {failed} do not, {unhashed} without a hash");
+    if failed > 0 {
+        return Err(anyhow::anyhow!("{failed} script(s) failed verification"));
     }
-    
+
     Ok(())
 }
 
-async fn show_instance_status(config_manager: &ConfigManager, instance_name: &str) -> Result<()> {
-    // Check if instance exists
-    if !std::path::Path::new(instance_name).exists() {
-        println!("  Instance '{instance_name}' not found");
+async fn handle_pull_command(module: &dyn Module, module_id: &str, instance: Option<String>, local_changes: LocalChangesMode, attribute_authors: bool, audit_log: bool) -> Result<()> {
+    let instance_name = instance.unwrap_or_else(|| "default".to_string());
+
+    // Acquire lock to prevent concurrent operations on the same instance
+    let _lock = InstanceLock::acquire(&instance_name)?;
+
+    let config_manager = ConfigManager::new();
+    let module_config = config_manager.load_module_config(&instance_name, module_id)?;
+
+    // Check if module is enabled
+    if !module_config.enabled {
+        println!("Module '{module_id}' is disabled in instance '{instance_name}'. Enable it in config.toml to use this command.");
         return Ok(());
     }
-    
-    // Git status for this instance (using instance-specific git repo)
-    match GitWrapper::new_for_instance(instance_name) {
-        Ok(git_wrapper) => {
-            let modified_files = git_wrapper.get_modified_files_in_current_repo()?;
-            
-            if modified_files.is_empty() {
-                println!("  Git: No modified files");
-            } else {
-                println!("  Git: {} modified files", modified_files.len());
-                for file in &modified_files {
-                    println!("    - {file}");
+
+    let tenant = module_config.fqdn.clone();
+    let commit_template = config_manager.load_commit_settings(&instance_name)?.template;
+    // Known [vars] values are written back as placeholders when reverse mapping is enabled
+    let reverse_templating = if config_manager.load_templating_settings(&instance_name)?.reverse_map {
+        Some(config_manager.load_templating(&instance_name)?)
+    } else {
+        None
+    };
+    let normaliser = config_manager.load_normaliser(&instance_name)?;
+    let archive_settings = config_manager.load_archive_settings(&instance_name)?;
+    let mut module_client = api::ModuleClient::new(module_config, module.base_api_path());
+    if archive_settings.raw_responses {
+        module_client = module_client.with_response_archive();
+    }
+    let yaml_parser = YamlParser::with_layout(config_manager.load_layout_settings(&instance_name)?);
+
+    // Platform branch mode commits straight to platform/<module> and leaves the worktree alone
+    let git_settings = config_manager.load_git_settings(&instance_name)?;
+    let platform_branch = git_settings.platform_branches
+        .then(|| format!("{}{module_id}", git_wrapper::PLATFORM_BRANCH_PREFIX));
+
+    // Protect uncommitted local edits before any file is overwritten
+    // Merge mode keeps (path, last pulled content, local content) for each edited file
+    let mut local_edits: Vec<git_wrapper::LocalEdit> = Vec::new();
+    let mut stashed = false;
+    let worktree_git = if platform_branch.is_none() { GitWrapper::new_for_instance(&instance_name).ok() } else { None };
+    if let Some(mut git_wrapper) = worktree_git {
+        // Edits set aside by an interrupted pull go back into the worktree before anything else
+        if let Some(edits) = git_wrapper.read_local_edits_backup(module_id)? {
+            println!("Recovering local edits set aside by an interrupted pull of {module_id}...");
+            merge_local_edits(&yaml_parser, &instance_name, edits)?;
+            git_wrapper.remove_local_edits_backup(module_id)?;
+        }
+
+        let modified_files = git_wrapper.get_locally_modified_files(module_id)?;
+        if !modified_files.is_empty() {
+            match local_changes {
+                LocalChangesMode::Abort => {
+                    println!("Uncommitted local edits found in {instance_name}/{module_id}:");
+                    for file in &modified_files {
+                        println!("  - {file}");
+                    }
+                    println!("Commit or discard them, or re-run with --local-changes stash|merge");
+                    return Err(anyhow::anyhow!("Pull aborted to avoid overwriting local edits"));
+                }
+                LocalChangesMode::Stash => {
+                    stashed = git_wrapper.stash_local_changes(&modified_files, &format!("gcgit: local edits before {module_id} pull"))?;
+                    if stashed {
+                        println!("Stashed {} locally edited file(s) before pulling", modified_files.len());
+                    }
+                }
+                LocalChangesMode::Merge => {
+                    for path in &modified_files {
+                        let base_content = git_wrapper.read_file_at_revision("HEAD", path)?;
+                        let local_content = std::fs::read_to_string(format!("{instance_name}/{path}")).ok();
+                        local_edits.push((path.clone(), base_content, local_content));
+                    }
+                    // Keep a copy on disk so the edits survive a failed or interrupted pull
+                    git_wrapper.write_local_edits_backup(module_id, &local_edits)?;
+                    println!("Set aside {} locally edited file(s) for merging after the pull", modified_files.len());
                 }
             }
         }
-        Err(_) => {
-            println!("  Git: No repository (run gcgit pull to initialise)");
+    }
+
+    let pulled = pull_module_objects(PullContext {
+        module,
+        module_id,
+        instance_name: &instance_name,
+        tenant: &tenant,
+        commit_template: commit_template.as_deref(),
+        config_manager: &config_manager,
+        module_client: &module_client,
+        yaml_parser: &yaml_parser,
+        normaliser: &normaliser,
+        reverse_templating: reverse_templating.as_ref(),
+        archive_settings: &archive_settings,
+        platform_branch: platform_branch.as_deref(),
+        local_edits: &local_edits,
+        attribute_authors,
+        audit_log,
+    }).await;
+
+    // Local edits are re-applied whether or not the pull succeeded
+    if stashed {
+        let mut git_wrapper = GitWrapper::new_for_instance(&instance_name)?;
+        match git_wrapper.pop_stashed_changes() {
+            Ok(()) => println!("Re-applied stashed local edits"),
+            Err(e) => println!("Warning: {e}. Your edits remain in the stash (see 'git stash list')"),
         }
     }
-    
-    // Module connectivity status - check all enabled modules dynamically
-    let module_registry = crate::modules::ModuleRegistry::load();
-    for module in module_registry.all_modules() {
-        let module_id = module.id();
-        
-        match config_manager.load_module_config(instance_name, module_id) {
-            Ok(module_config) => {
-                if module_config.enabled {
-                    let module_client = api::ModuleClient::new(module_config, module.base_api_path());
-                    match module_client.test_connectivity().await {
-                        Ok(_) => println!("  {}: Connected", module_id.to_uppercase()),
-                        Err(e) => println!("  {}: Connection failed - {e}", module_id.to_uppercase()),
+
+    if !local_edits.is_empty() {
+        merge_local_edits(&yaml_parser, &instance_name, local_edits)?;
+        GitWrapper::new_for_instance(&instance_name)?.remove_local_edits_backup(module_id)?;
+    }
+
+    pulled
+}
+
+/// Settings and clients a module pull works with
+struct PullContext<'a> {
+    module: &'a dyn Module,
+    module_id: &'a str,
+    instance_name: &'a str,
+    tenant: &'a str,
+    commit_template: Option<&'a str>,
+    config_manager: &'a ConfigManager,
+    module_client: &'a api::ModuleClient,
+    yaml_parser: &'a YamlParser,
+    normaliser: &'a normalise::Normaliser,
+    reverse_templating: Option<&'a templating::Templating>,
+    archive_settings: &'a config::ArchiveSettings,
+    platform_branch: Option<&'a str>,
+    local_edits: &'a [git_wrapper::LocalEdit],
+    attribute_authors: bool,
+    audit_log: bool,
+}
+
+/// Pull every content type of a module, write the objects and commit them
+/// Local edits set aside beforehand are reset first, so the commit records platform state only.
+async fn pull_module_objects(ctx: PullContext<'_>) -> Result<()> {
+    let PullContext {
+        module, module_id, instance_name, tenant, commit_template, config_manager, module_client,
+        yaml_parser, normaliser, reverse_templating, archive_settings, platform_branch, local_edits,
+        attribute_authors, audit_log,
+    } = ctx;
+
+    // Reset edited files so the commit records platform state only; files new since HEAD stay on disk and are unstaged
+    if !local_edits.is_empty() {
+        let git_wrapper = GitWrapper::new_for_instance(instance_name)?;
+        let (tracked, new): (Vec<_>, Vec<_>) = local_edits.iter()
+            .partition(|(_, base_content, _)| base_content.is_some());
+        let tracked: Vec<String> = tracked.into_iter().map(|(path, _, _)| path.clone()).collect();
+        let new: Vec<String> = new.into_iter().map(|(path, _, _)| path.clone()).collect();
+        if !tracked.is_empty() {
+            git_wrapper.restore_from_revision("HEAD", &tracked)?;
+        }
+        git_wrapper.unstage_paths(&new)?;
+    }
+
+    // Pull each content type defined in the module
+    let content_types = module.content_types();
+
+    let mut _total_pulled = 0;
+    let mut pulled_files = Vec::new();
+    // Directories of content types pulled in full, where files not pulled are objects deleted on the platform
+    let mut mirrored_dirs: Vec<String> = Vec::new();
+    let mut branch_files: Vec<(String, Vec<u8>)> = Vec::new();
+    // Platform user who last modified each pulled file, for per-author commits
+    let mut file_authors: std::collections::HashMap<String, String> = std::collections::HashMap::new();
+
+    for content_def in content_types {
+        println!("Pulling {}...", content_def.name);
+        match module_client.pull_content_type(&content_def).await {
+            Ok(objects) => {
+                println!("  Found {} {}(s)", objects.len(), content_def.name);
+
+                let files = pulled_content_type_files(yaml_parser, normaliser, reverse_templating, instance_name, module_id, &content_def, &objects)?;
+                for file in files {
+                    if is_object_file(&file.path) {
+                        println!("  Pulled: {instance_name}/{}", file.path);
                     }
+                    if platform_branch.is_none() {
+                        write_instance_file(instance_name, &file.path, &file.content)?;
+                    }
+                    if let Some(user) = file.author {
+                        file_authors.insert(file.path.clone(), user);
+                    }
+                    if platform_branch.is_some() {
+                        branch_files.push((file.path.clone(), file.content));
+                    }
+                    pulled_files.push(file.path);
+                }
+                if module_client.pull_was_complete(content_def.name) {
+                    mirrored_dirs.push(format!("{module_id}/{}", content_def.name));
                 } else {
-                    println!("  {}: Disabled", module_id.to_uppercase());
+                    println!("  Some {} could not be pulled - objects missing from the pull are kept", content_def.name);
                 }
+                _total_pulled += objects.len();
             }
-            Err(_) => {
-                // Module not configured - skip silently
+            Err(e) => {
+                println!("  WARNING: Failed to pull {} - {}", content_def.name, e);
+                println!("  (This endpoint may not be available on your instance)");
             }
         }
     }
-    
+
+    // Save the raw responses outside the instance repository
+    if archive_settings.raw_responses {
+        let responses = module_client.take_raw_responses();
+        if !responses.is_empty() {
+            let base = archive_settings.path.as_deref().unwrap_or(archive::DEFAULT_DIR);
+            let path = archive::write(&archive::module_dir(base, instance_name, module_id), chrono::Utc::now(), &responses)?;
+            println!("\nArchived {} raw response(s) to {}", responses.len(), path.display());
+        }
+    }
+
+    // Platform branch mode - commit pulled state without touching the worktree
+    if let Some(branch) = &platform_branch {
+        if !pulled_files.is_empty() || !mirrored_dirs.is_empty() {
+            println!("\nProcessing pulled files for branch {branch}...");
+            match GitWrapper::new_for_instance(instance_name) {
+                Ok(mut git_wrapper) => {
+                    git_wrapper.set_signer(config_manager.load_commit_signer(instance_name)?);
+                    // A new platform branch starts from the last pull on the checked-out branch, or
+                    // with a parentless commit, so human and local commits stay off it
+                    if !git_wrapper.branch_exists(branch) {
+                        let last_pull = git_wrapper.find_latest_commit("HEAD", |message| commit_message::is_pull_commit(message, module_id, instance_name))?;
+                        if let Some((commit_id, _)) = last_pull {
+                            git_wrapper.create_branch(branch, commit_id)?;
+                        }
+                    }
+                    let previous_pull = if audit_log { previous_pull_time(&git_wrapper, branch, module_id, instance_name)? } else { None };
+                    let mut changed_files = git_wrapper.changed_files_on_branch(branch, &branch_files)?;
+                    let pulled: std::collections::HashSet<&String> = pulled_files.iter().collect();
+                    for path in git_wrapper.branch_files_under(branch, &mirrored_dirs)? {
+                        if !pulled.contains(&path) {
+                            println!("  Removed on platform: {instance_name}/{path}");
+                            changed_files.push((path, git2::Delta::Deleted));
+                        }
+                    }
+                    if changed_files.is_empty() {
+                        println!("Successfully processed {} pulled files to instance Git repository", pulled_files.len());
+                        println!("  No Git changes detected - {branch} already matches the platform");
+                    } else if sidecar::object_changes(&changed_files).is_empty() {
+                        println!("Successfully processed {} pulled files to instance Git repository", pulled_files.len());
+                        println!("  Only platform metadata changed - {branch} left as it is");
+                    } else {
+                        let mut result = Ok(());
+                        let mut last_commit = None;
+                        for (user, group) in group_changes_by_author(&changed_files, &file_authors, attribute_authors) {
+                            let commit_info = commit_message::PullCommitInfo {
+                                module_id,
+                                instance_name,
+                                tenant,
+                                changes: &group,
+                            };
+                            let commit_message = commit_message::pull_commit_message(commit_template, &commit_info);
+                            let author = user.map(|user| commit_message::author_for_user(&user, tenant));
+                            let group_files: Vec<(String, Vec<u8>)> = branch_files.iter()
+                                .filter(|(path, _)| group.iter().any(|(changed, _)| changed == path))
+                                .cloned()
+                                .collect();
+                            let group_removed: Vec<String> = group.iter()
+                                .filter(|(_, delta)| *delta == git2::Delta::Deleted)
+                                .map(|(path, _)| path.clone())
+                                .collect();
+                            match git_wrapper.commit_files_to_branch(branch, &group_files, &group_removed, &commit_message, author.as_ref()) {
+                                Ok(commit_id) => last_commit = Some(commit_id),
+                                Err(e) => {
+                                    result = Err(e);
+                                    break;
+                                }
+                            }
+                        }
+                        match result {
+                            Ok(()) => {
+                                let file_word = if changed_files.len() == 1 { "file" } else { "files" };
+                                println!("Successfully processed {} pulled files to instance Git repository", pulled_files.len());
+                                println!("  {} {file_word} changed and committed to {branch}", changed_files.len());
+                                println!("  Run 'gcgit platform merge --instance {instance_name} --module {module_id}' to merge into your working branch");
+                                if let (true, Some(commit_id)) = (audit_log, last_commit) {
+                                    let identifiers = changed_object_identifiers(&git_wrapper, yaml_parser, branch, &changed_files);
+                                    attach_audit_note(module_client, &git_wrapper, commit_id, previous_pull, &identifiers).await;
+                                }
+                            }
+                            Err(e) => println!("Warning: Failed to commit changes: {e}"),
+                        }
+                    }
+                }
+                Err(e) => {
+                    println!("Warning: Failed to initialise Git repository for instance: {e}");
+                }
+            }
+        }
+        return Ok(());
+    }
+
+    // Auto-commit pulled changes using Git's native change detection
+    if !pulled_files.is_empty() || !mirrored_dirs.is_empty() {
+        println!("\nProcessing pulled files for Git repository...");
+
+        match GitWrapper::new_for_instance(instance_name) {
+            Ok(mut git_wrapper) => {
+                git_wrapper.set_signer(config_manager.load_commit_signer(instance_name)?);
+                let previous_pull = if audit_log { previous_pull_time(&git_wrapper, "HEAD", module_id, instance_name)? } else { None };
+
+                // Tracked files missing from a complete pull belong to objects deleted on the platform
+                let pulled: std::collections::HashSet<&String> = pulled_files.iter().collect();
+                let mut removed_files = Vec::new();
+                for path in git_wrapper.tracked_files_under(&mirrored_dirs)? {
+                    if !pulled.contains(&path) {
+                        let file_path = format!("{instance_name}/{path}");
+                        if std::path::Path::new(&file_path).exists() {
+                            std::fs::remove_file(&file_path)
+                                .with_context(|| format!("Failed to remove {file_path}"))?;
+                        }
+                        println!("  Removed on platform: {file_path}");
+                        removed_files.push(path);
+                    }
+                }
+
+                // Use Git's native change detection - much faster than API calls
+                match git_wrapper.has_changes_after_add(&pulled_files, &removed_files) {
+                    // Metadata-only changes are left uncommitted until an object changes
+                    Ok((true, _, changed_files)) if sidecar::object_changes(&changed_files).is_empty() => {
+                        let paths: Vec<String> = changed_files.iter().map(|(path, _)| path.clone()).collect();
+                        git_wrapper.unstage_paths(&paths)?;
+                        println!("Successfully processed {} pulled files to instance Git repository", pulled_files.len());
+                        println!("  Only platform metadata changed - left uncommitted");
+                    }
+                    Ok((true, changed_count, changed_files)) => {
+                        let mut result = Ok(());
+                        let mut last_commit = None;
+                        if attribute_authors {
+                            for (user, group) in group_changes_by_author(&changed_files, &file_authors, true) {
+                                let commit_info = commit_message::PullCommitInfo {
+                                    module_id,
+                                    instance_name,
+                                    tenant,
+                                    changes: &group,
+                                };
+                                let commit_message = commit_message::pull_commit_message(commit_template, &commit_info);
+                                let author = user.map(|user| commit_message::author_for_user(&user, tenant));
+                                let paths: Vec<String> = group.iter().map(|(path, _)| path.clone()).collect();
+                                match git_wrapper.commit_staged_paths(&paths, &commit_message, author.as_ref()) {
+                                    Ok(commit_id) => last_commit = Some(commit_id),
+                                    Err(e) => {
+                                        result = Err(e);
+                                        break;
+                                    }
+                                }
+                            }
+                        } else {
+                            let commit_info = commit_message::PullCommitInfo {
+                                module_id,
+                                instance_name,
+                                tenant,
+                                changes: &changed_files,
+                            };
+                            let commit_message = commit_message::pull_commit_message(commit_template, &commit_info);
+                            match git_wrapper.commit(&commit_message) {
+                                Ok(commit_id) => last_commit = Some(commit_id),
+                                Err(e) => result = Err(e),
+                            }
+                        }
+
+                        if let Err(e) = result {
+                            println!("Warning: Failed to commit changes: {e}");
+                        } else {
+                            let file_word = if changed_count == 1 { "file" } else { "files" };
+                            println!("Successfully processed {} pulled files to instance Git repository", pulled_files.len());
+                            println!("  {changed_count} {file_word} actually changed and committed");
+                            if let (true, Some(commit_id)) = (audit_log, last_commit) {
+                                let identifiers = changed_object_identifiers(&git_wrapper, yaml_parser, "HEAD", &changed_files);
+                                attach_audit_note(module_client, &git_wrapper, commit_id, previous_pull, &identifiers).await;
+                            }
+                        }
+                    }
+                    Ok((false, _, _)) => {
+                        println!("Successfully processed {} pulled files to instance Git repository", pulled_files.len());
+                        println!("  No Git changes detected - objects serialise to identical YAML");
+                    }
+                    Err(e) => {
+                        println!("Warning: Failed to check for changes: {e}");
+                    }
+                }
+            }
+            Err(e) => {
+                println!("Warning: Failed to initialise Git repository for instance: {e}");
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Time of the previous pull of a module, found through the trailers of pull commits
+fn previous_pull_time(git_wrapper: &GitWrapper, rev: &str, module_id: &str, instance_name: &str) -> Result<Option<chrono::DateTime<chrono::Utc>>> {
+    let previous = git_wrapper.find_latest_commit(rev, |message| commit_message::is_pull_commit(message, module_id, instance_name))?;
+    Ok(previous.map(|(_, time)| time))
+}
+
+/// Names and IDs of the objects behind changed files, as committed at a revision
+/// Deleted objects are known only by their file name, with and without underscores for spaces.
+fn changed_object_identifiers(git_wrapper: &GitWrapper, yaml_parser: &YamlParser, rev: &str, changes: &[git_wrapper::FileChange]) -> Vec<String> {
+    let mut identifiers = Vec::new();
+    for (path, _) in sidecar::object_changes(changes) {
+        let object = git_wrapper.read_file_at_revision(rev, &path).ok().flatten()
+            .and_then(|content| yaml_parser.parse_content(&content, &path).ok());
+        match object {
+            Some(object) => {
+                identifiers.extend(object.name);
+                identifiers.push(object.id);
+            }
+            None => {
+                let stem = path.rsplit('/').next().unwrap_or(&path).trim_end_matches(".yaml");
+                identifiers.push(stem.replace('_', " "));
+                identifiers.push(stem.to_string());
+            }
+        }
+    }
+    identifiers
+}
+
+/// Attach management audit log entries about the changed objects, recorded since the previous pull, as a note on the pull commit
+/// Failures only produce warnings since the pull itself has already been committed.
+async fn attach_audit_note(module_client: &api::ModuleClient, git_wrapper: &GitWrapper, commit_id: git2::Oid, previous_pull: Option<chrono::DateTime<chrono::Utc>>, identifiers: &[String]) {
+    let Some(since) = previous_pull else {
+        println!("  No previous pull found - audit log annotation starts with the next pull");
+        return;
+    };
+
+    let until = chrono::Utc::now();
+    match module_client.fetch_management_audit_logs(since, until).await {
+        Ok(records) => {
+            let entries: Vec<audit::AuditEntry> = records.iter()
+                .map(audit::AuditEntry::from_api_record)
+                .filter(|entry| entry.mentions_any(identifiers))
+                .collect();
+            let note = audit::format_note(since, until, &entries);
+            match git_wrapper.add_note(audit::AUDIT_NOTES_REF, commit_id, &note) {
+                Ok(()) => println!("  Attached {} audit log entries (view with 'git notes --ref=gcgit-audit show')", entries.len()),
+                Err(e) => println!("Warning: {e}"),
+            }
+        }
+        Err(e) => println!("Warning: Failed to fetch management audit log - {e}"),
+    }
+}
+
+/// Split changed files into commit groups keyed by the platform user who last modified them
+/// Files without a known user, or all files when attribution is off, form a single unattributed group.
+fn group_changes_by_author(
+    changes: &[git_wrapper::FileChange],
+    file_authors: &std::collections::HashMap<String, String>,
+    attribute_authors: bool,
+) -> std::collections::BTreeMap<Option<String>, Vec<git_wrapper::FileChange>> {
+    let mut groups: std::collections::BTreeMap<Option<String>, Vec<git_wrapper::FileChange>> = std::collections::BTreeMap::new();
+    for change in changes {
+        let user = if attribute_authors { file_authors.get(&change.0).cloned() } else { None };
+        groups.entry(user).or_default().push(change.clone());
+    }
+
+    // Files that belong to no object, such as metadata indexes, go with the first object commit
+    let objectless: Vec<Option<String>> = groups.iter()
+        .filter(|(_, group)| sidecar::object_changes(group).is_empty())
+        .map(|(user, _)| user.clone())
+        .collect();
+    if objectless.len() < groups.len() {
+        let moved: Vec<git_wrapper::FileChange> = objectless.iter()
+            .filter_map(|user| groups.remove(user))
+            .flatten()
+            .collect();
+        if let Some(group) = groups.values_mut().next() {
+            group.extend(moved);
+        }
+    }
+    groups
+}
+
+/// Re-derive object YAML from an archived pull, writing it to the worktree for review
+fn handle_rederive_command(module: &dyn Module, instance_name: &str, archive_file: Option<String>, content_type: Option<String>) -> Result<()> {
+    let module_id = module.id();
+    let _lock = InstanceLock::acquire(instance_name)?;
+
+    let config_manager = ConfigManager::new();
+    let archive_settings = config_manager.load_archive_settings(instance_name)?;
+    let archive_path = match archive_file {
+        Some(path) => std::path::PathBuf::from(path),
+        None => {
+            let base = archive_settings.path.as_deref().unwrap_or(archive::DEFAULT_DIR);
+            archive::latest(&archive::module_dir(base, instance_name, module_id))?
+                .ok_or_else(|| anyhow::anyhow!("No archived responses for {module_id} on {instance_name}. Enable raw_responses under [archive] in config.toml and pull first"))?
+        }
+    };
+    println!("Re-deriving {module_id} objects from {}", archive_path.display());
+
+    let reverse_templating = if config_manager.load_templating_settings(instance_name)?.reverse_map {
+        Some(config_manager.load_templating(instance_name)?)
+    } else {
+        None
+    };
+    let normaliser = config_manager.load_normaliser(instance_name)?;
+    let module_client = api::ModuleClient::new(config_manager.load_module_config(instance_name, module_id)?, module.base_api_path());
+    let yaml_parser = YamlParser::with_layout(config_manager.load_layout_settings(instance_name)?);
+
+    // Responses of a content type (pages, or a script list and its code) are combined before the objects are laid out
+    let responses = archive::read(&archive_path)?;
+    let mut responses_by_type: Vec<(modules::ContentTypeDefinition, Vec<&archive::RawResponse>)> = Vec::new();
+    for response in &responses {
+        let name = &response.content_type;
+        if content_type.as_ref().is_some_and(|wanted| wanted != name) {
+            continue;
+        }
+        match responses_by_type.iter_mut().find(|(existing, _)| existing.name == name) {
+            Some((_, existing)) => existing.push(response),
+            None => {
+                let content_def = module.content_types()
+                    .into_iter()
+                    .find(|content_def| content_def.name == name)
+                    .ok_or_else(|| anyhow::anyhow!("Archived content type '{name}' is not part of module '{module_id}'"))?;
+                responses_by_type.push((content_def, vec![response]));
+            }
+        }
+    }
+    let objects_by_type = responses_by_type.iter()
+        .map(|(content_def, responses)| Ok((content_def, module_client.objects_from_archive(content_def, responses)?)))
+        .collect::<Result<Vec<_>>>()?;
+    if objects_by_type.is_empty() {
+        return Err(anyhow::anyhow!("{} holds no matching responses", archive_path.display()));
+    }
+
+    let mut written = 0;
+    for (content_def, objects) in &objects_by_type {
+        println!("\n{}: {} object(s)", content_def.name, objects.len());
+        for file in pulled_content_type_files(&yaml_parser, &normaliser, reverse_templating.as_ref(), instance_name, module_id, content_def, objects)? {
+            if is_object_file(&file.path) {
+                println!("  Re-derived: {instance_name}/{}", file.path);
+            }
+            write_instance_file(instance_name, &file.path, &file.content)?;
+            written += 1;
+        }
+    }
+
+    println!("\nWrote {written} file(s). Review the changes with 'git -C {instance_name} diff' before committing");
+    Ok(())
+}
+
+/// A file produced by pulling a content type, relative to the instance
+struct PulledFile {
+    path: String,
+    content: Vec<u8>,
+    /// Platform user who last modified the object the file belongs to
+    author: Option<String>,
+}
+
+/// Lay out the pulled objects of one content type as files
+/// Applies normalisation and reverse templating, resolves file name collisions, and adds
+/// sidecar files and the metadata index as configured.
+fn pulled_content_type_files(
+    yaml_parser: &YamlParser,
+    normaliser: &normalise::Normaliser,
+    reverse_templating: Option<&templating::Templating>,
+    instance_name: &str,
+    module_id: &str,
+    content_def: &modules::ContentTypeDefinition,
+    objects: &[XsiamObject],
+) -> Result<Vec<PulledFile>> {
+    // Objects whose fallback IDs collide get distinct IDs before they name files
+    let mut objects = objects.to_vec();
+    for id in XsiamObject::disambiguate_fallback_ids(&mut objects) {
+        println!("  WARNING: several {} objects share the identity fields behind {id} - telling them apart by content", content_def.name);
+    }
+
+    // Build base filenames and detect collisions
+    let base_names: Vec<String> = objects.iter().map(|obj| content_def.file_stem(obj)).collect();
+
+    // Count occurrences of each base name
+    let mut name_counts: std::collections::HashMap<&str, usize> = std::collections::HashMap::new();
+    for name in &base_names {
+        *name_counts.entry(name.as_str()).or_insert(0) += 1;
+    }
+
+    let mut files = Vec::new();
+    let mut metadata_entries = metadata_index::MetadataIndex::new();
+    for (object, base_name) in objects.iter().zip(base_names.iter()) {
+        // Disambiguate colliding names by appending the object ID
+        let filename = if name_counts.get(base_name.as_str()).copied().unwrap_or(1) > 1 {
+            format!("{}_{}", base_name, object.id)
+        } else {
+            base_name.clone()
+        };
+
+        let file_path = format!("{}/{}/{}/{}.yaml", instance_name, module_id, content_def.name, filename);
+        let relative_path = format!("{}/{}/{}.yaml", module_id, content_def.name, filename);
+        let mut object = normaliser.apply(object);
+        if let Some(templating) = reverse_templating {
+            // Overlaid fields fall back to the values in the base file on disk
+            let base = if templating.has_overlay(&relative_path) && std::path::Path::new(&file_path).exists() {
+                Some(yaml_parser.parse_file(&file_path)?)
+            } else {
+                None
+            };
+            object = templating.reverse_map(&object, &relative_path, base.as_ref())?;
+        }
+        if yaml_parser.uses_metadata_index() {
+            metadata_entries.insert(format!("{filename}.yaml"), object.metadata.clone());
+        }
+        if object.has_fallback_id() {
+            let identity = match content_def.id_fallback {
+                modules::IdFallback::ContentHash { fields, .. } => fields.join(", "),
+                modules::IdFallback::Fixed(_) => String::new(),
+            };
+            println!("  WARNING: {file_path} has no platform ID - using ID {} derived from {identity} (changes if those fields change)", object.id);
+        }
+
+        // The YAML plus any sidecar files, such as script code
+        let author = object.last_modified_by();
+        for (path, content) in yaml_parser.object_files(&object, &relative_path)? {
+            files.push(PulledFile { path, content, author: author.clone() });
+        }
+    }
+
+    // Metadata of the whole content type goes to its index file
+    if yaml_parser.uses_metadata_index() {
+        files.push(PulledFile {
+            path: metadata_index::index_path(module_id, content_def.name),
+            content: metadata_index::render(&metadata_entries)?.into_bytes(),
+            author: None,
+        });
+    }
+
+    Ok(files)
+}
+
+/// Write a file relative to the instance directory, creating its parent directories
+fn write_instance_file(instance_name: &str, path: &str, content: &[u8]) -> Result<()> {
+    let file_path = format!("{instance_name}/{path}");
+    if let Some(parent) = std::path::Path::new(&file_path).parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+    }
+    std::fs::write(&file_path, content)
+        .with_context(|| format!("Failed to write file: {file_path}"))
+}
+
+/// Re-apply local edits set aside before a pull on top of the newly pulled platform state
+fn merge_local_edits(yaml_parser: &YamlParser, instance_name: &str, local_edits: Vec<git_wrapper::LocalEdit>) -> Result<()> {
+    println!("\nMerging local edits with pulled platform state...");
+    let mut conflicted_files = 0;
+
+    for (path, base_content, local_content) in local_edits {
+        let file_path = format!("{instance_name}/{path}");
+        let remote_content = std::fs::read_to_string(&file_path).ok();
+
+        let Some(local_content) = local_content else {
+            // Deleted locally - only keep the deletion if the platform has not changed the object
+            if remote_content == base_content {
+                if remote_content.is_some() {
+                    std::fs::remove_file(&file_path)
+                        .with_context(|| format!("Failed to remove {file_path}"))?;
+                }
+                println!("  Kept local deletion: {file_path}");
+            } else {
+                conflicted_files += 1;
+                println!("  CONFLICT: {file_path} (deleted locally but changed on the platform - keeping platform version)");
+            }
+            continue;
+        };
+
+        let Some(remote_content) = remote_content else {
+            // Not written by the pull, so there is nothing to merge with
+            std::fs::write(&file_path, &local_content)
+                .with_context(|| format!("Failed to write {file_path}"))?;
+            println!("  Kept local edit: {file_path}");
+            continue;
+        };
+
+        // Sidecar files such as script code are merged as whole files
+        if !is_object_file(&path) {
+            std::fs::write(&file_path, &local_content)
+                .with_context(|| format!("Failed to write {file_path}"))?;
+            if base_content.as_ref() == Some(&remote_content) {
+                println!("  Kept local edit: {file_path}");
+            } else {
+                conflicted_files += 1;
+                println!("  CONFLICT: {file_path} (changed locally and on the platform - kept local file, platform version is committed)");
+            }
+            continue;
+        }
+
+        let parsed = (
+            yaml_parser.parse_content(&local_content, &file_path),
+            yaml_parser.parse_content(&remote_content, &file_path),
+        );
+        let (local, remote) = match parsed {
+            (Ok(local), Ok(remote)) => (local, remote),
+            _ => {
+                conflicted_files += 1;
+                std::fs::write(&file_path, &local_content)
+                    .with_context(|| format!("Failed to write {file_path}"))?;
+                println!("  CONFLICT: {file_path} (could not parse for merging - kept local file, platform version is committed)");
+                continue;
+            }
+        };
+        let base = base_content.and_then(|content| yaml_parser.parse_content(&content, &file_path).ok());
+
+        let outcome = merge::three_way_merge(base.as_ref(), &local, &remote);
+        yaml_parser.write_file(&file_path, &outcome.merged)?;
+
+        if outcome.conflicts.is_empty() {
+            println!("  Merged: {file_path}");
+        } else {
+            conflicted_files += 1;
+            println!("  CONFLICT: {file_path} (kept local value for: {})", outcome.conflicts.join(", "));
+        }
+    }
+
+    if conflicted_files > 0 {
+        println!("{conflicted_files} file(s) changed both locally and on the platform - review with 'git diff' before committing");
+    } else {
+        println!("Local edits merged cleanly and left uncommitted on top of the pulled state");
+    }
+
+    Ok(())
+}
+
+async fn handle_init_command(instance: String) -> Result<()> {
+    let config_manager = ConfigManager::new();
+    config_manager.init_instance(&instance)?;
+    
+    println!("Initialised instance: {instance}");
+    println!("Please edit {instance}/config.toml with your API credentials");
+    println!("  Configure modules.xsiam for XSIAM platform access");
+    println!("  Configure modules.appsec for Application Security platform access");
+    
+    Ok(())
+}
+
+async fn handle_status_command(instance: Option<String>) -> Result<()> {
+    let config_manager = ConfigManager::new();
+    
+    match instance {
+        Some(instance_name) => {
+            println!("Status for instance: {instance_name}");
+            show_instance_status(&config_manager, &instance_name).await?;
+        }
+        None => {
+            println!("Status for all instances:");
+            // Get all instance directories
+            let instances = get_all_instances()?;
+            for instance_name in instances {
+                println!("\n=== {instance_name} ===");
+                show_instance_status(&config_manager, &instance_name).await?;
+            }
+        }
+    }
+    
+    Ok(())
+}
+
+async fn handle_validate_command(instance: Option<String>, files: Vec<String>) -> Result<()> {
+    let yaml_parser = YamlParser::new();
+    let module_registry = ModuleRegistry::load();
+    
+    // Collect all content type names from all modules for validation
+    let all_content_types: Vec<&str> = module_registry.all_modules()
+        .iter()
+        .flat_map(|module| module.content_types())
+        .map(|ct| ct.name)
+        .collect();
+    
+    // Determine files to validate
+    let files_to_validate = if !files.is_empty() {
+        files
+    } else if let Some(instance_name) = &instance {
+        // Get all YAML files in the specified instance across all modules
+        let mut instance_files = Vec::new();
+        for module in module_registry.all_modules() {
+            let module_dir = format!("{}/{}", instance_name, module.id());
+            if let Ok(files) = yaml_parser.get_local_files(&module_dir, &all_content_types) {
+                instance_files.extend(files);
+            }
+        }
+        instance_files
+    } else {
+        // Get all YAML files in all instances
+        let instances = get_all_instances()?;
+        let mut all_files = Vec::new();
+        for inst in instances {
+            for module in module_registry.all_modules() {
+                let module_dir = format!("{}/{}", inst, module.id());
+                if let Ok(files) = yaml_parser.get_local_files(&module_dir, &all_content_types) {
+                    all_files.extend(files);
+                }
+            }
+        }
+        all_files
+    };
+    
+    if files_to_validate.is_empty() {
+        println!("No YAML files found to validate");
+        return Ok(());
+    }
+    
+    println!("Validating {} files...", files_to_validate.len());
+    let mut validation_errors = 0;
+    
+    for file_path in files_to_validate {
+        print!("  Checking {file_path}... ");
+        
+        match yaml_parser.parse_file(&file_path) {
+            Ok(xsiam_object) => {
+                // Validate content type is supported by checking against all registered modules
+                if all_content_types.contains(&xsiam_object.content_type.as_str()) {
+                    println!("Valid");
+                } else {
+                    println!("INVALID: Unsupported content type: {}", xsiam_object.content_type);
+                    validation_errors += 1;
+                }
+            }
+            Err(e) => {
+                println!("ERROR: {e}");
+                validation_errors += 1;
+            }
+        }
+    }
+    
+    if validation_errors > 0 {
+        println!("\n{validation_errors} validation errors found");
+        return Err(anyhow::anyhow!("Validation failed"));
+    } else {
+        println!("\nAll files are valid");
+    }
+    
+    Ok(())
+}
+
+async fn handle_restore_command(instance: Option<String>, rev: String, push: bool, paths: Vec<String>) -> Result<()> {
+    let instance_name = instance.unwrap_or_else(|| "default".to_string());
+
+    // Acquire lock to prevent concurrent operations on the same instance
+    let _lock = InstanceLock::acquire(&instance_name)?;
+
+    let git_wrapper = GitWrapper::new_for_instance(&instance_name)?;
+    let yaml_parser = YamlParser::new();
+
+    // Accept paths relative to the instance or prefixed with the instance directory
+    let instance_prefix = format!("{instance_name}/");
+    let mut paths: Vec<String> = paths.iter()
+        .map(|path| path.strip_prefix(&instance_prefix).unwrap_or(path).trim_end_matches('/').to_string())
+        .collect();
+
+    // Restoring an object also restores its sidecar files, as referenced then and now
+    let mut sidecar_paths = Vec::new();
+    for path in paths.iter().filter(|path| is_object_file(path)) {
+        let dir = path.rsplit_once('/').map(|(dir, _)| dir).unwrap_or_default();
+        let versions = [
+            git_wrapper.read_file_at_revision(&rev, path)?,
+            std::fs::read_to_string(format!("{instance_name}/{path}")).ok(),
+        ];
+        for content in versions.into_iter().flatten() {
+            if let Ok(object) = yaml_parser.parse_content(&content, path) {
+                for file_name in sidecar::referenced_files(&object) {
+                    sidecar_paths.push(format!("{dir}/{file_name}"));
+                }
+            }
+        }
+    }
+    for path in sidecar_paths {
+        if !paths.contains(&path) {
+            paths.push(path);
+        }
+    }
+
+    // Only verified commits may be applied to the platform when signing is enforced
+    let config_manager = ConfigManager::new();
+    if push && config_manager.requires_signed_commits(&instance_name)? {
+        if let Some(verifier) = config_manager.load_signature_verifier(&instance_name)? {
+            let signer = git_wrapper.verify_commit(&rev, &verifier)
+                .context("Refusing to push a revision without a trusted signature")?;
+            println!("Verified signature on {rev} from {signer}");
+        }
+    }
+
+    // Objects that disappear need their current content to be deleted from the platform
+    let module_registry = ModuleRegistry::load();
+    let mut removed_objects = Vec::new();
+    if push {
+        let preview = git_wrapper.preview_restore(&rev, &paths)?;
+
+        // Refuse before touching the worktree if any object cannot be written to the platform
+        for (path, action) in &preview {
+            let object_path = sidecar::owning_yaml_path(path).unwrap_or_else(|| path.clone());
+            let mut segments = object_path.split('/');
+            let (Some(module_id), Some(content_type)) = (segments.next(), segments.next()) else {
+                continue;
+            };
+            let Some(content_def) = module_registry.get(module_id)
+                .and_then(|module| module.content_types().into_iter().find(|ct| ct.name == content_type)) else {
+                continue;
+            };
+            // A removed sidecar changes its object rather than deleting it
+            let (endpoint, operation) = if *action == git_wrapper::RestoreAction::Removed && *path == object_path {
+                (content_def.delete_endpoint, "deleting")
+            } else {
+                (content_def.write_endpoint, "writing")
+            };
+            if endpoint.is_none() && is_object_file(&object_path) {
+                return Err(anyhow::anyhow!(
+                    "Cannot push {object_path}: {operation} {content_type} is not supported by the platform API. Run without --push, or restore only content types that can be pushed."
+                ));
+            }
+        }
+
+        for (path, action) in preview {
+            if action == git_wrapper::RestoreAction::Removed && is_object_file(&path) {
+                let file_path = format!("{instance_name}/{path}");
+                removed_objects.push((path, yaml_parser.parse_file(&file_path)?));
+            }
+        }
+    }
+
+    let changes = git_wrapper.restore_from_revision(&rev, &paths)?;
+    if changes.is_empty() {
+        println!("Nothing to restore - files already match {rev}");
+        return Ok(());
+    }
+
+    for (path, action) in &changes {
+        match action {
+            git_wrapper::RestoreAction::Restored => println!("  Restored: {instance_name}/{path}"),
+            git_wrapper::RestoreAction::Removed => println!("  Removed: {instance_name}/{path}"),
+        }
+    }
+    println!("Restored {} file(s) from {rev}", changes.len());
+
+    if !push {
+        println!("Review the restored files, then commit them or run again with --push to apply them to the platform");
+        return Ok(());
+    }
+
+    println!("\nPushing restored objects to the platform...");
+    let mut clients: std::collections::HashMap<String, api::ModuleClient> = std::collections::HashMap::new();
+    let templating = config_manager.load_templating(&instance_name)?;
+    let mut failures = 0;
+
+    // A restored sidecar means its object changed, even if the YAML did not
+    let mut restored_objects: Vec<String> = Vec::new();
+    for (path, action) in &changes {
+        if *action == git_wrapper::RestoreAction::Restored {
+            let object_path = sidecar::owning_yaml_path(path).unwrap_or_else(|| path.clone());
+            let exists = std::path::Path::new(&format!("{instance_name}/{object_path}")).exists();
+            if is_object_file(&object_path) && exists && !restored_objects.contains(&object_path) {
+                restored_objects.push(object_path);
+            }
+        }
+    }
+
+    let mut operations = Vec::new();
+    for path in restored_objects {
+        let object = yaml_parser.parse_file(&format!("{instance_name}/{path}"))?;
+        operations.push((path, object, false));
+    }
+    for (path, object) in removed_objects {
+        operations.push((path, object, true));
+    }
+
+    for (path, object, is_removal) in operations {
+        let module_id = path.split('/').next().unwrap_or_default().to_string();
+        let Some(module) = module_registry.get(&module_id) else {
+            println!("  Skipped: {path} (not in a module directory)");
+            continue;
+        };
+        let content_types = module.content_types();
+        let Some(content_def) = content_types.iter().find(|ct| ct.name == object.content_type) else {
+            println!("  Skipped: {path} (unknown content type '{}')", object.content_type);
+            continue;
+        };
+
+        if !clients.contains_key(&module_id) {
+            let module_config = config_manager.load_module_config(&instance_name, &module_id)?;
+            if !module_config.enabled {
+                return Err(anyhow::anyhow!("Module '{module_id}' is disabled in instance '{instance_name}'. Enable it in config.toml to push restored objects."));
+            }
+            clients.insert(module_id.clone(), api::ModuleClient::new(module_config, module.base_api_path()));
+        }
+        let module_client = &clients[&module_id];
+
+        let result = if is_removal {
+            module_client.delete_object(&object, content_def).await
+        } else {
+            match templating.render(&object, &path) {
+                Ok(rendered) => module_client.create_or_update_object(&rendered, content_def).await,
+                Err(e) => Err(e),
+            }
+        };
+
+        match result {
+            Ok(()) if is_removal => println!("  Deleted on platform: {path}"),
+            Ok(()) => println!("  Pushed: {path}"),
+            Err(e) => {
+                failures += 1;
+                println!("  FAILED: {path} - {e}");
+            }
+        }
+    }
+
+    if failures > 0 {
+        return Err(anyhow::anyhow!("{failures} object(s) could not be written to the platform"));
+    }
+
+    Ok(())
+}
+
+async fn handle_platform_command(command: PlatformCommands) -> Result<()> {
+    match command {
+        PlatformCommands::Merge { instance, module } => {
+            let instance_name = instance.unwrap_or_else(|| "default".to_string());
+
+            // Acquire lock to prevent concurrent operations on the same instance
+            let _lock = InstanceLock::acquire(&instance_name)?;
+
+            let mut git_wrapper = GitWrapper::new_for_instance(&instance_name)?;
+            git_wrapper.set_signer(ConfigManager::new().load_commit_signer(&instance_name)?);
+            let branches = platform_branches(&git_wrapper, module.as_deref())?;
+
+            let local_edits = git_wrapper.get_locally_modified_files("")?;
+            if !local_edits.is_empty() {
+                return Err(anyhow::anyhow!(
+                    "Instance '{instance_name}' has {} uncommitted file(s). Commit or discard them before merging platform changes",
+                    local_edits.len()
+                ));
+            }
+
+            let target = git_wrapper.current_branch().unwrap_or_else(|| "HEAD".to_string());
+            for branch in branches {
+                match git_wrapper.merge_branch(&branch)? {
+                    git_wrapper::BranchMergeResult::UpToDate => println!("{target} is already up to date with {branch}"),
+                    git_wrapper::BranchMergeResult::FastForward => println!("Fast-forwarded {target} to {branch}"),
+                    git_wrapper::BranchMergeResult::Merged => println!("Merged {branch} into {target}"),
+                    git_wrapper::BranchMergeResult::Conflicts(paths) => {
+                        println!("Merging {branch} into {target} stopped with conflicts:");
+                        for path in &paths {
+                            println!("  - {path}");
+                        }
+                        println!("Resolve the conflicts in {instance_name}/ and commit with Git to finish the merge");
+                        return Err(anyhow::anyhow!("Merge of {branch} has conflicts"));
+                    }
+                }
+            }
+        }
+        PlatformCommands::Diff { instance, module } => {
+            let instance_name = instance.unwrap_or_else(|| "default".to_string());
+            let git_wrapper = GitWrapper::new_for_instance(&instance_name)?;
+            let branches = platform_branches(&git_wrapper, module.as_deref())?;
+            let target = git_wrapper.current_branch().unwrap_or_else(|| "HEAD".to_string());
+
+            for branch in branches {
+                let module_id = branch.trim_start_matches(git_wrapper::PLATFORM_BRANCH_PREFIX);
+                let (ahead, behind) = git_wrapper.ahead_behind(&branch)?;
+                println!("{target} vs {branch}: {ahead} commit(s) ahead, {behind} commit(s) behind");
+
+                let changes = git_wrapper.diff_branch_to_head(&branch, module_id)?;
+                if changes.is_empty() {
+                    println!("  No differences in {module_id}/ - the platform matches {target}");
+                    continue;
+                }
+                for (path, delta) in changes {
+                    let label = match delta {
+                        git2::Delta::Added => "Only local",
+                        git2::Delta::Deleted => "Only on platform",
+                        _ => "Differs",
+                    };
+                    println!("  {label}: {path}");
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn handle_snapshot_command(instance: Option<String>, tag: String, message: Option<String>) -> Result<()> {
+    let instance_name = instance.unwrap_or_else(|| "default".to_string());
+
+    // Acquire lock to prevent concurrent operations on the same instance
+    let _lock = InstanceLock::acquire(&instance_name)?;
+
+    let git_wrapper = GitWrapper::new_for_instance(&instance_name)?;
+    let message = message.unwrap_or_else(|| {
+        format!("Snapshot of instance '{instance_name}' taken {}", chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true))
+    });
+    let tag_id = git_wrapper.create_tag(&tag, &message)?;
+    println!("Created snapshot tag '{tag}' ({tag_id}) in instance '{instance_name}'");
+
+    Ok(())
+}
+
+fn handle_report_command(instance: Option<String>, from: String, to: String, format: ReportFormat, output: Option<String>) -> Result<()> {
+    let instance_name = instance.unwrap_or_else(|| "default".to_string());
+
+    let git_wrapper = GitWrapper::new_for_instance(&instance_name)?;
+    let yaml_parser = YamlParser::new();
+    let changes = report::build_report(&git_wrapper, &yaml_parser, &from, &to)?;
+
+    let title = format!("Changes in {instance_name}: {from} to {to}");
+    let rendered = match format {
+        ReportFormat::Md => report::render_markdown(&title, &changes),
+        ReportFormat::Html => report::render_html(&title, &changes),
+    };
+
+    match output {
+        Some(path) => {
+            std::fs::write(&path, rendered)
+                .with_context(|| format!("Failed to write report: {path}"))?;
+            println!("Wrote report of {} changed object(s) to {path}", changes.len());
+        }
+        None => print!("{rendered}"),
+    }
+
+    Ok(())
+}
+
+fn handle_compare_command(left: String, right: String, module: Option<String>) -> Result<()> {
+    for instance_name in [&left, &right] {
+        if !std::path::Path::new(instance_name).is_dir() {
+            return Err(anyhow::anyhow!("Instance directory '{instance_name}' does not exist"));
+        }
+    }
+
+    let module_registry = ModuleRegistry::load();
+    let modules: Vec<&dyn Module> = match &module {
+        Some(module_id) => vec![module_registry.get(module_id)
+            .ok_or_else(|| anyhow::anyhow!("Module '{module_id}' not found"))?],
+        None => module_registry.all_modules(),
+    };
+
+    let yaml_parser = YamlParser::new();
+    let mut drifted_types = 0;
+    let mut summary = Vec::new();
+
+    println!("Comparing {left} (left) with {right} (right)");
+    for module in modules {
+        let mut left_objects = compare::load_module_objects(&yaml_parser, &left, module)?;
+        let mut right_objects = compare::load_module_objects(&yaml_parser, &right, module)?;
+
+        for content_def in module.content_types() {
+            let left_list = left_objects.remove(content_def.name).unwrap_or_default();
+            let right_list = right_objects.remove(content_def.name).unwrap_or_default();
+            if left_list.is_empty() && right_list.is_empty() {
+                continue;
+            }
+
+            let comparison = compare::compare_objects(&yaml_parser, &content_def, left_list, right_list)?;
+            summary.push((
+                format!("{}/{}", module.id(), content_def.name),
+                comparison.identical,
+                comparison.differing.len(),
+                comparison.only_left.len(),
+                comparison.only_right.len(),
+            ));
+            if !comparison.has_drift() {
+                continue;
+            }
+            drifted_types += 1;
+
+            println!("\n{}/{}:", module.id(), content_def.name);
+            for object in &comparison.only_left {
+                println!("  Only in {left}: {}", compare::display_name(object));
+            }
+            for object in &comparison.only_right {
+                println!("  Only in {right}: {}", compare::display_name(object));
+            }
+            for (left_object, _, fields) in &comparison.differing {
+                println!("  Differs: {}", compare::display_name(left_object));
+                for field in fields {
+                    println!("    - {}", field.path);
+                }
+            }
+        }
+    }
+
+    println!("\n{:<40} {:>9} {:>9} {:>9} {:>9}", "Content type", "Same", "Differ", "Left", "Right");
+    for (name, identical, differing, only_left, only_right) in &summary {
+        println!("{name:<40} {identical:>9} {differing:>9} {only_left:>9} {only_right:>9}");
+    }
+
+    if drifted_types == 0 {
+        println!("\nNo drift - {left} and {right} match");
+    } else {
+        println!("\nDrift found in {drifted_types} content type(s)");
+    }
+
+    Ok(())
+}
+
+fn handle_baseline_check_command(instance: Option<String>, baseline: Option<String>, module: Option<String>, fail_on: baseline::Severity) -> Result<()> {
+    let instance_name = instance.unwrap_or_else(|| "default".to_string());
+    let config_manager = ConfigManager::new();
+
+    let baseline_dir = match baseline {
+        Some(path) => path,
+        None => config_manager.load_baseline_settings(&instance_name)?.path
+            .ok_or_else(|| anyhow::anyhow!("No baseline given. Pass --baseline PATH or set [baseline] path in {instance_name}/config.toml"))?,
+    };
+    let baseline_dir = baseline_dir.trim_end_matches('/').to_string();
+    if !std::path::Path::new(&baseline_dir).is_dir() {
+        return Err(anyhow::anyhow!("Baseline directory '{baseline_dir}' does not exist"));
+    }
+
+    let module_registry = ModuleRegistry::load();
+    let modules: Vec<&dyn Module> = match &module {
+        Some(module_id) => vec![module_registry.get(module_id)
+            .ok_or_else(|| anyhow::anyhow!("Module '{module_id}' not found"))?],
+        None => module_registry.all_modules(),
+    };
+
+    println!("Checking {instance_name} against baseline {baseline_dir}");
+    let yaml_parser = YamlParser::new();
+    let deviations = baseline::check(&yaml_parser, &baseline_dir, &instance_name, &modules)?;
+
+    for deviation in &deviations {
+        match &deviation.kind {
+            baseline::DeviationKind::Missing => {
+                println!("{:<9} {} ({}): missing", deviation.severity.label(), deviation.path, deviation.name);
+            }
+            baseline::DeviationKind::Differs(fields) => {
+                println!("{:<9} {} ({}): differs", deviation.severity.label(), deviation.path, deviation.name);
+                for field in fields {
+                    let render = |value: &Option<serde_json::Value>| value.as_ref().map_or("(absent)".to_string(), |v| v.to_string());
+                    println!("          - {}: expected {}, found {}", field.path, render(&field.old), render(&field.new));
+                }
+            }
+        }
+    }
+
+    let count = |severity| deviations.iter().filter(|d| d.severity == severity).count();
+    println!(
+        "\nSummary: {} critical, {} high, {} medium, {} low",
+        count(baseline::Severity::Critical),
+        count(baseline::Severity::High),
+        count(baseline::Severity::Medium),
+        count(baseline::Severity::Low)
+    );
+
+    let violations = deviations.iter().filter(|d| d.severity >= fail_on).count();
+    if violations > 0 {
+        return Err(anyhow::anyhow!("{violations} baseline violation(s) at or above {} severity", fail_on.label().to_lowercase()));
+    }
+    if deviations.is_empty() {
+        println!("{instance_name} meets the baseline");
+    }
+
+    Ok(())
+}
+
+fn handle_promote_command(from: String, to: String, content_type: String, select: Vec<String>) -> Result<()> {
+    if from == to {
+        return Err(anyhow::anyhow!("Source and target instance must differ"));
+    }
+
+    let module_registry = ModuleRegistry::load();
+    let module = module_registry.all_modules()
+        .into_iter()
+        .find(|module| module.content_types().iter().any(|ct| ct.name == content_type))
+        .ok_or_else(|| anyhow::anyhow!("Content type '{content_type}' not found in any module"))?;
+    let module_id = module.id();
+    let content_def = module.content_types()
+        .into_iter()
+        .find(|ct| ct.name == content_type)
+        .ok_or_else(|| anyhow::anyhow!("Content type '{content_type}' not found in module '{module_id}'"))?;
+
+    // Acquire locks on both instances
+    let _source_lock = InstanceLock::acquire(&from)?;
+    let _target_lock = InstanceLock::acquire(&to)?;
+
+    // Objects are written using the target instance's layout
+    let yaml_parser = YamlParser::with_layout(ConfigManager::new().load_layout_settings(&to)?);
+    let source_objects = promote::load_objects(&yaml_parser, &from, module_id, &content_type)?;
+    let target_objects = promote::load_objects(&yaml_parser, &to, module_id, &content_type)?;
+    let selected = promote::select_objects(&source_objects, &select)?;
+
+    let source_commit = GitWrapper::new_for_instance(&from)?.head_commit_id().map(|id| id.to_string());
+    let target_git = GitWrapper::new_for_instance(&to)?;
+
+    let target_prefix = format!("{to}/");
+    let mut staged = Vec::new();
+    let mut promoted_count = 0;
+    for source in selected {
+        let counterpart = promote::find_counterpart(&source.object, &target_objects);
+        let promoted = promote::remap_for_target(
+            &content_def,
+            &source.object,
+            counterpart.map(|local| &local.object),
+            &from,
+            source_commit.as_deref(),
+        );
+
+        let target_path = match counterpart {
+            Some(local) => local.path.clone(),
+            None => promote::new_object_path(&to, module_id, &content_type, &source.path)?,
+        };
+        if counterpart.is_none() && std::path::Path::new(&target_path).exists() {
+            return Err(anyhow::anyhow!("{target_path} already exists for a different object; rename it before promoting"));
+        }
+
+        let written = yaml_parser.write_file(&target_path, &promoted)?;
+        let name = compare::display_name(&promoted);
+        match counterpart {
+            Some(_) => println!("  Updated: {target_path} ({name})"),
+            None => println!("  Created: {target_path} ({name}, new on {to})"),
+        }
+        for path in &written {
+            staged.push(path.strip_prefix(&target_prefix).unwrap_or(path).to_string());
+        }
+        promoted_count += 1;
+    }
+
+    target_git.add_files(&staged)?;
+    println!("Promoted {promoted_count} object(s) from {from} to {to}; changes are staged in {to}/");
+    println!("Review with 'git -C {to} diff --cached', then commit and push them to the platform");
+
+    Ok(())
+}
+
+fn handle_verify_command(instance: Option<String>, rev: String) -> Result<()> {
+    let instance_name = instance.unwrap_or_else(|| "default".to_string());
+
+    let verifier = ConfigManager::new().load_signature_verifier(&instance_name)?
+        .ok_or_else(|| anyhow::anyhow!("No allowed_signers configured. Add a [signing] block to {instance_name}/config.toml"))?;
+
+    let git_wrapper = GitWrapper::new_for_instance(&instance_name)?;
+    let signer = git_wrapper.verify_commit(&rev, &verifier)?;
+    println!("Good signature on {rev} from {signer}");
+
+    Ok(())
+}
+
+/// Push the checked-out branch and any platform branches to the instance's Git remote
+fn push_instance_repo(instance_name: &str) -> Result<()> {
+    // Acquire lock to prevent concurrent operations on the same instance
+    let _lock = InstanceLock::acquire(instance_name)?;
+
+    let config_manager = ConfigManager::new();
+    let remote = config_manager.load_remote_settings(instance_name)?
+        .ok_or_else(|| anyhow::anyhow!("No Git remote configured. Add a [remote] block with a url to {instance_name}/config.toml"))?;
+    let credentials = remote.credentials()?;
+
+    let git_wrapper = GitWrapper::new_for_instance(instance_name)?;
+    git_wrapper.configure_remote(&remote.name, &remote.url)?;
+
+    let mut branches = Vec::new();
+    if let Some(current) = git_wrapper.current_branch().filter(|b| git_wrapper.branch_exists(b)) {
+        let target = remote.branch.clone().unwrap_or_else(|| current.clone());
+        branches.push((current, target));
+    }
+    for platform_branch in git_wrapper.branches_with_prefix(git_wrapper::PLATFORM_BRANCH_PREFIX)? {
+        if !branches.iter().any(|(local, _)| local == &platform_branch) {
+            branches.push((platform_branch.clone(), platform_branch));
+        }
+    }
+
+    if branches.is_empty() {
+        println!("Nothing to push - instance '{instance_name}' has no commits yet");
+        return Ok(());
+    }
+
+    println!("Pushing instance '{instance_name}' to {} ({})...", remote.name, remote.url);
+    git_wrapper.push_branches(&remote.name, &branches, &credentials)?;
+    for (local, target) in &branches {
+        println!("  Pushed {local} -> {}/{target}", remote.name);
+    }
+
+    // Audit log notes travel with the history they describe
+    if git_wrapper.reference_exists(audit::AUDIT_NOTES_REF) {
+        let refspec = format!("{0}:{0}", audit::AUDIT_NOTES_REF);
+        git_wrapper.push_refs(&remote.name, &[refspec], &credentials)?;
+        println!("  Pushed {}", audit::AUDIT_NOTES_REF);
+    }
+
+    Ok(())
+}
+
+/// Resolve the platform branches to operate on, optionally limited to one module
+fn platform_branches(git_wrapper: &GitWrapper, module: Option<&str>) -> Result<Vec<String>> {
+    let branches = match module {
+        Some(module_id) => {
+            let branch = format!("{}{module_id}", git_wrapper::PLATFORM_BRANCH_PREFIX);
+            if !git_wrapper.branch_exists(&branch) {
+                return Err(anyhow::anyhow!("Branch '{branch}' does not exist. Enable platform_branches under [git] in config.toml and pull first"));
+            }
+            vec![branch]
+        }
+        None => git_wrapper.branches_with_prefix(git_wrapper::PLATFORM_BRANCH_PREFIX)?,
+    };
+
+    if branches.is_empty() {
+        return Err(anyhow::anyhow!("No platform branches found. Enable platform_branches under [git] in config.toml and pull first"));
+    }
+
+    Ok(branches)
+}
+
+/// Check whether a path relative to the instance points at an object YAML file
+fn is_object_file(path: &str) -> bool {
+    (path.ends_with(".yaml") || path.ends_with(".yml")) && path.split('/').count() == 3 && !metadata_index::is_index_path(path)
+}
+
+async fn show_instance_status(config_manager: &ConfigManager, instance_name: &str) -> Result<()> {
+    // Check if instance exists
+    if !std::path::Path::new(instance_name).exists() {
+        println!("  Instance '{instance_name}' not found");
+        return Ok(());
+    }
+    
+    // Git status for this instance (using instance-specific git repo)
+    let git_wrapper = match GitWrapper::new_for_instance(instance_name) {
+        Ok(git_wrapper) => {
+            let modified_files = git_wrapper.get_modified_files_in_current_repo()?;
+            
+            if modified_files.is_empty() {
+                println!("  Git: No modified files");
+            } else {
+                println!("  Git: {} modified files", modified_files.len());
+                for file in &modified_files {
+                    println!("    - {file}");
+                }
+            }
+            show_git_remote_status(config_manager, instance_name, &git_wrapper);
+            Some(git_wrapper)
+        }
+        Err(_) => {
+            println!("  Git: No repository (run gcgit pull to initialise)");
+            None
+        }
+    };
+    
+    // Module connectivity and sync state - check all enabled modules dynamically
+    let git_settings = config_manager.load_git_settings(instance_name).unwrap_or_default();
+    let module_registry = crate::modules::ModuleRegistry::load();
+    for module in module_registry.all_modules() {
+        let module_id = module.id();
+        
+        match config_manager.load_module_config(instance_name, module_id) {
+            Ok(module_config) => {
+                if module_config.enabled {
+                    let module_client = api::ModuleClient::new(module_config, module.base_api_path());
+                    let connected = match module_client.test_connectivity().await {
+                        Ok(_) => {
+                            println!("  {}: Connected", module_id.to_uppercase());
+                            true
+                        }
+                        Err(e) => {
+                            println!("  {}: Connection failed - {e}", module_id.to_uppercase());
+                            false
+                        }
+                    };
+                    let platform_rev = if git_settings.platform_branches {
+                        format!("{}{module_id}", git_wrapper::PLATFORM_BRANCH_PREFIX)
+                    } else {
+                        "HEAD".to_string()
+                    };
+                    let client = connected.then_some(&module_client);
+                    show_module_sync_status(module, instance_name, git_wrapper.as_ref(), &platform_rev, client).await?;
+                } else {
+                    println!("  {}: Disabled", module_id.to_uppercase());
+                }
+            }
+            Err(_) => {
+                // Module not configured - skip silently
+            }
+        }
+    }
+    
+    Ok(())
+}
+
+/// Print ahead/behind counts against the configured Git remote, fetching it first
+/// Falls back to the last fetched state when the remote cannot be reached.
+fn show_git_remote_status(config_manager: &ConfigManager, instance_name: &str, git_wrapper: &GitWrapper) {
+    let Ok(Some(remote)) = config_manager.load_remote_settings(instance_name) else {
+        return;
+    };
+    let Some(current) = git_wrapper.current_branch().filter(|b| git_wrapper.branch_exists(b)) else {
+        return;
+    };
+    let target = remote.branch.clone().unwrap_or(current);
+
+    let fetched = git_wrapper.configure_remote(&remote.name, &remote.url)
+        .and_then(|_| remote.credentials())
+        .and_then(|credentials| git_wrapper.fetch_branch(&remote.name, &target, &credentials));
+    let stale = if fetched.is_err() { " (as of last fetch)" } else { "" };
+
+    match git_wrapper.ahead_behind_remote(&remote.name, &target) {
+        Ok(Some((ahead, behind))) => println!("  Remote {}/{target}: {ahead} ahead, {behind} behind{stale}", remote.name),
+        Ok(None) => println!("  Remote {}/{target}: not pushed yet", remote.name),
+        Err(e) => println!("  Remote {}/{target}: {e}", remote.name),
+    }
+    if let Err(e) = fetched {
+        println!("    Warning: {e}");
+    }
+}
+
+/// Print last pull, object counts, local edits and platform drift for one module
+/// Drift is only checked when the platform is reachable and the module has been pulled before.
+async fn show_module_sync_status(module: &dyn Module, instance_name: &str, git_wrapper: Option<&GitWrapper>, platform_rev: &str, module_client: Option<&api::ModuleClient>) -> Result<()> {
+    let module_id = module.id();
+    let yaml_parser = YamlParser::new();
+    let module_dir = format!("{instance_name}/{module_id}");
+
+    let last_pull = match git_wrapper {
+        Some(git_wrapper) => git_wrapper.find_latest_commit(platform_rev, |message| commit_message::is_pull_commit(message, module_id, instance_name))?,
+        None => None,
+    };
+    match &last_pull {
+        Some((commit_id, time)) => {
+            let short_id: String = commit_id.to_string().chars().take(7).collect();
+            println!("    Last pull: {} ({short_id})", time.format("%Y-%m-%d %H:%M UTC"));
+        }
+        None => println!("    Last pull: never"),
+    }
+
+    let mut local_counts = Vec::new();
+    for content_def in module.content_types() {
+        let count = yaml_parser.get_local_files(&module_dir, &[content_def.name])?.len();
+        local_counts.push((content_def, count));
+    }
+    let counts: Vec<String> = local_counts.iter()
+        .filter(|(_, count)| *count > 0)
+        .map(|(content_def, count)| format!("{} {count}", content_def.name))
+        .collect();
+    println!("    Objects: {}", if counts.is_empty() { "none".to_string() } else { counts.join(", ") });
+
+    // Local edits are uncommitted changes plus committed changes the platform has not seen
+    if let Some(git_wrapper) = git_wrapper {
+        let uncommitted = git_wrapper.get_locally_modified_files(module_id)?.len();
+        let committed = if platform_rev != "HEAD" {
+            if git_wrapper.branch_exists(platform_rev) { git_wrapper.diff_branch_to_head(platform_rev, module_id)?.len() } else { 0 }
+        } else if let Some((commit_id, _)) = &last_pull {
+            git_wrapper.diff_revisions(&commit_id.to_string(), "HEAD")?.iter()
+                .filter(|(path, _)| path.starts_with(&format!("{module_id}/")))
+                .count()
+        } else {
+            0
+        };
+        if uncommitted == 0 && committed == 0 {
+            println!("    Local edits: none");
+        } else {
+            println!("    Local edits: {uncommitted} uncommitted, {committed} committed since last pull");
+        }
+    }
+
+    let (Some(module_client), Some((_, pulled_at))) = (module_client, &last_pull) else {
+        return Ok(());
+    };
+    let mut drifted = Vec::new();
+    for (content_def, local_count) in &local_counts {
+        // Listing is enough to spot changes; script code is not downloaded
+        match module_client.list_content_type(content_def).await {
+            Ok(objects) => {
+                let drift = status::RemoteDrift::assess(&objects, *local_count, *pulled_at);
+                if drift.has_drift() {
+                    drifted.push(format!("{} ({})", content_def.name, drift.describe()));
+                }
+            }
+            Err(_) => {
+                // Endpoint unavailable on this tenant - nothing to compare
+            }
+        }
+    }
+    if drifted.is_empty() {
+        println!("    Platform: no changes since last pull");
+    } else {
+        println!("    Platform: changed since last pull - {}", drifted.join("; "));
+    }
+
+    Ok(())
+}
+
+/// Re-run the current command for every instance in a group and print a summary table
+fn handle_group_command(group: &str) -> Result<()> {
+    let global_config = ConfigManager::new().load_global_config()?;
+    let instances = global_config.group_instances(group)?;
+    let max_parallel = global_config.max_parallel.unwrap_or(fleet::DEFAULT_MAX_PARALLEL);
+
+    let program = std::env::current_exe().context("Failed to locate the gcgit executable")?;
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args = fleet::child_args(&args);
+
+    println!("Running '{}' for {} instance(s) in group '{group}' ({max_parallel} at a time)", args.join(" "), instances.len());
+    let runs = fleet::run_across(&program, &args, &instances, max_parallel, |run| {
+        println!("\n=== {} ===", run.instance);
+        print!("{}", run.output);
+    })?;
+
+    println!("\n{:<24} {:<7} {:>8}  Summary", "Instance", "Result", "Time");
+    for run in &runs {
+        println!(
+            "{:<24} {:<7} {:>7.1}s  {}",
+            run.instance,
+            if run.success { "ok" } else { "FAILED" },
+            run.duration.as_secs_f64(),
+            run.summary
+        );
+    }
+
+    let failures = runs.iter().filter(|run| !run.success).count();
+    if failures > 0 {
+        return Err(anyhow::anyhow!("{failures} of {} instance(s) in group '{group}' failed", runs.len()));
+    }
+
     Ok(())
 }
 
@@ -0,0 +1,136 @@
+// SPDX-FileCopyrightText: GoCortexIO
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+// Field-level three-way merge of objects
+// Used by pull to combine uncommitted local edits with new platform state
+
+use serde_json::{Map, Value};
+use std::collections::BTreeSet;
+
+use crate::types::XsiamObject;
+
+/// Result of merging a local edit with a new remote version
+#[derive(Debug)]
+pub struct MergeOutcome {
+    pub merged: XsiamObject,
+    /// Dotted paths of fields changed on both sides; the local value is kept for these
+    pub conflicts: Vec<String>,
+}
+
+/// Merge local and remote versions of an object against the last pulled version
+/// Fields changed on only one side take that side's value. Fields changed differently
+/// on both sides keep the local value and are reported as conflicts.
+pub fn three_way_merge(base: Option<&XsiamObject>, local: &XsiamObject, remote: &XsiamObject) -> MergeOutcome {
+    let mut conflicts = Vec::new();
+
+    let base_value = base.map(XsiamObject::fields);
+    let merged_fields = merge_value(
+        base_value.as_ref(),
+        Some(&local.fields()),
+        Some(&remote.fields()),
+        "",
+        &mut conflicts,
+    ).unwrap_or_else(|| Value::Object(Map::new()));
+
+    let merged = remote.with_fields(merged_fields);
+
+    MergeOutcome { merged, conflicts }
+}
+
+/// Merge a single value; None means the field is absent on that side
+fn merge_value(base: Option<&Value>, local: Option<&Value>, remote: Option<&Value>, path: &str, conflicts: &mut Vec<String>) -> Option<Value> {
+    if local == remote {
+        return local.cloned();
+    }
+    if local == base {
+        return remote.cloned();
+    }
+    if remote == base {
+        return local.cloned();
+    }
+
+    // Both sides changed - recurse into maps so edits to different keys can coexist
+    if let (Some(Value::Object(local_map)), Some(Value::Object(remote_map))) = (local, remote) {
+        let base_map = base.and_then(|b| b.as_object());
+        let keys: BTreeSet<&String> = local_map.keys().chain(remote_map.keys()).collect();
+
+        let mut merged = Map::new();
+        for key in keys {
+            let child_path = if path.is_empty() { key.clone() } else { format!("{path}.{key}") };
+            let merged_child = merge_value(
+                base_map.and_then(|m| m.get(key)),
+                local_map.get(key),
+                remote_map.get(key),
+                &child_path,
+                conflicts,
+            );
+            if let Some(value) = merged_child {
+                merged.insert(key.clone(), value);
+            }
+        }
+        return Some(Value::Object(merged));
+    }
+
+    conflicts.push(path.to_string());
+    local.cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn object(description: &str, content: Value) -> XsiamObject {
+        let mut object = XsiamObject::new("1".to_string(), "Rule".to_string(), "biocs".to_string());
+        object.description = description.to_string();
+        object.content = content.as_object().unwrap().iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+        object
+    }
+
+    #[test]
+    fn test_non_overlapping_changes_are_combined() {
+        let base = object("old", json!({"severity": "low", "enabled": true}));
+        let local = object("old", json!({"severity": "high", "enabled": true}));
+        let remote = object("new", json!({"severity": "low", "enabled": false}));
+
+        let outcome = three_way_merge(Some(&base), &local, &remote);
+        assert!(outcome.conflicts.is_empty());
+        assert_eq!(outcome.merged.description, "new");
+        assert_eq!(outcome.merged.content["severity"], json!("high"));
+        assert_eq!(outcome.merged.content["enabled"], json!(false));
+    }
+
+    #[test]
+    fn test_nested_maps_merge_per_key() {
+        let base = object("", json!({"schedule": {"interval": 10, "timezone": "UTC"}}));
+        let local = object("", json!({"schedule": {"interval": 5, "timezone": "UTC"}}));
+        let remote = object("", json!({"schedule": {"interval": 10, "timezone": "GMT"}}));
+
+        let outcome = three_way_merge(Some(&base), &local, &remote);
+        assert!(outcome.conflicts.is_empty());
+        assert_eq!(outcome.merged.content["schedule"], json!({"interval": 5, "timezone": "GMT"}));
+    }
+
+    #[test]
+    fn test_conflicting_changes_keep_local() {
+        let base = object("", json!({"severity": "low"}));
+        let local = object("", json!({"severity": "high"}));
+        let remote = object("", json!({"severity": "medium"}));
+
+        let outcome = three_way_merge(Some(&base), &local, &remote);
+        assert_eq!(outcome.conflicts, vec!["severity".to_string()]);
+        assert_eq!(outcome.merged.content["severity"], json!("high"));
+    }
+
+    #[test]
+    fn test_removals_follow_the_changing_side() {
+        let base = object("", json!({"a": 1, "b": 2}));
+        let local = object("", json!({"a": 1}));
+        let remote = object("", json!({"a": 1, "b": 2, "c": 3}));
+
+        let outcome = three_way_merge(Some(&base), &local, &remote);
+        assert!(outcome.conflicts.is_empty());
+        assert!(!outcome.merged.content.contains_key("b"));
+        assert_eq!(outcome.merged.content["c"], json!(3));
+    }
+}
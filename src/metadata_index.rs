@@ -0,0 +1,131 @@
+// SPDX-FileCopyrightText: GoCortexIO
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+// Per content type metadata index
+// With [layout] metadata_index enabled, object YAML is written without its metadata block.
+// The metadata of every object of a content type is kept in one index file instead,
+// module/.metadata/<content_type>.yaml, keyed by the object's file name.
+
+use anyhow::{Context, Result};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use crate::types::ObjectMetadata;
+
+/// Directory within a module holding the metadata index files
+pub const INDEX_DIR: &str = ".metadata";
+
+/// Metadata of the objects of one content type, keyed by YAML file name
+pub type MetadataIndex = BTreeMap<String, ObjectMetadata>;
+
+/// Index path for a content type, e.g. xsiam/.metadata/biocs.yaml
+pub fn index_path(module_dir: &str, content_type: &str) -> String {
+    format!("{module_dir}/{INDEX_DIR}/{content_type}.yaml")
+}
+
+/// Whether a path is a metadata index file rather than an object
+pub fn is_index_path(path: &str) -> bool {
+    path.split('/').any(|segment| segment == INDEX_DIR)
+}
+
+/// Serialise an index for writing
+pub fn render(index: &MetadataIndex) -> Result<String> {
+    serde_yaml_ng::to_string(index).context("Failed to serialise metadata index")
+}
+
+/// Index path and key for an object YAML at module/content_type/file.yaml
+fn locate(yaml_path: &str) -> Option<(String, String)> {
+    let path = Path::new(yaml_path);
+    let file_name = path.file_name()?.to_str()?;
+    let type_dir = path.parent()?;
+    let content_type = type_dir.file_name()?.to_str()?;
+    let module_dir = type_dir.parent()?.to_str()?;
+    Some((index_path(module_dir, content_type), file_name.to_string()))
+}
+
+/// Parsed index files, so reading the objects of a content type parses its index once
+/// An entry is re-read when the file's modification time or length changes.
+#[derive(Default)]
+pub struct IndexCache {
+    indexes: Mutex<HashMap<String, CachedIndex>>,
+}
+
+struct CachedIndex {
+    modified: Option<SystemTime>,
+    len: u64,
+    index: MetadataIndex,
+}
+
+impl IndexCache {
+    /// Metadata recorded in the index for an object YAML in the worktree, if any
+    pub fn lookup(&self, yaml_path: &str) -> Result<Option<ObjectMetadata>> {
+        let Some((index_path, key)) = locate(yaml_path) else {
+            return Ok(None);
+        };
+        let Ok(file_info) = fs::metadata(&index_path) else {
+            return Ok(None);
+        };
+        let modified = file_info.modified().ok();
+
+        let mut indexes = self.indexes.lock()
+            .map_err(|_| anyhow::anyhow!("Metadata index cache is poisoned"))?;
+        let cached = indexes.get(&index_path)
+            .filter(|cached| cached.modified == modified && cached.len == file_info.len());
+        if let Some(cached) = cached {
+            return Ok(cached.index.get(&key).cloned());
+        }
+
+        let content = fs::read_to_string(&index_path)
+            .with_context(|| format!("Failed to read metadata index: {index_path}"))?;
+        let index: MetadataIndex = serde_yaml_ng::from_str(&content)
+            .with_context(|| format!("Failed to parse metadata index: {index_path}"))?;
+        let metadata = index.get(&key).cloned();
+        indexes.insert(index_path, CachedIndex { modified, len: file_info.len(), index });
+        Ok(metadata)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_index_location() {
+        assert_eq!(
+            locate("prod/xsiam/biocs/Rule.yaml"),
+            Some(("prod/xsiam/.metadata/biocs.yaml".to_string(), "Rule.yaml".to_string()))
+        );
+        assert!(is_index_path("xsiam/.metadata/biocs.yaml"));
+        assert!(!is_index_path("xsiam/biocs/Rule.yaml"));
+    }
+
+    #[test]
+    fn test_lookup_reads_entry_for_file() {
+        let dir = "test_metadata_index_lookup";
+        let _ = fs::remove_dir_all(dir);
+
+        let mut metadata = ObjectMetadata { version: "3".to_string(), ..ObjectMetadata::default() };
+        metadata.additional.insert("code_sha256".to_string(), serde_json::json!("abc"));
+        let index = MetadataIndex::from([("Rule.yaml".to_string(), metadata.clone())]);
+        fs::create_dir_all(format!("{dir}/xsiam/.metadata")).unwrap();
+        fs::write(index_path(&format!("{dir}/xsiam"), "biocs"), render(&index).unwrap()).unwrap();
+
+        let cache = IndexCache::default();
+        let found = cache.lookup(&format!("{dir}/xsiam/biocs/Rule.yaml"));
+        let missing = cache.lookup(&format!("{dir}/xsiam/biocs/Other.yaml"));
+
+        // A rewritten index is read again
+        let updated = ObjectMetadata { version: "4".to_string(), ..metadata.clone() };
+        let index = MetadataIndex::from([("Rule.yaml".to_string(), updated.clone()), ("Other.yaml".to_string(), updated.clone())]);
+        fs::write(index_path(&format!("{dir}/xsiam"), "biocs"), render(&index).unwrap()).unwrap();
+        let refound = cache.lookup(&format!("{dir}/xsiam/biocs/Other.yaml"));
+        let _ = fs::remove_dir_all(dir);
+
+        assert_eq!(found.unwrap(), Some(metadata));
+        assert_eq!(missing.unwrap(), None);
+        assert_eq!(refound.unwrap(), Some(updated));
+    }
+}
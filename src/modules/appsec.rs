@@ -5,7 +5,7 @@
 // Supports 7 content types: applications, policies, rules, repositories, integrations,
 // application_configuration, application_criteria
 
-use super::{Module, ContentTypeDefinition, PullStrategy};
+use super::{Module, ContentTypeDefinition, IdFallback, IdentityField, PullStrategy};
 
 pub struct AppSecModule;
 
@@ -28,86 +28,149 @@ impl Module for AppSecModule {
             ContentTypeDefinition {
                 name: "applications",
                 get_endpoint: "appsec/v1/application",
+                write_endpoint: None,
+                delete_endpoint: None,
                 pull_strategy: PullStrategy::Paginated {
                     page_param: "page",
                     page_size_param: "pageSize",
                     page_size: 100,
                 },
-                id_field: "id",
+                id_fields: &[IdentityField::Any("id")],
+                id_fallback: IdFallback::ContentHash { prefix: "object", fields: &["name"] },
+                stable_ids: false,
+                tenant_id_field: None,
+                name_fields: &["name"],
+                excluded_fields: &[],
+                filename_template: "{name}",
                 request_body: None,
                 response_path: Some("data"),
+                set_paths: &[],
             },
             
             // Policies - Security policies for threat detection (returns array at root)
             ContentTypeDefinition {
                 name: "policies",
                 get_endpoint: "appsec/v1/policies",
+                write_endpoint: None,
+                delete_endpoint: None,
                 pull_strategy: PullStrategy::JsonCollection,
-                id_field: "id",
+                id_fields: &[IdentityField::Any("id")],
+                id_fallback: IdFallback::ContentHash { prefix: "object", fields: &["name"] },
+                stable_ids: false,
+                tenant_id_field: None,
+                name_fields: &["name"],
+                excluded_fields: &[],
+                filename_template: "{name}",
                 request_body: None,
                 response_path: None,
+                set_paths: &[],
             },
             
             // Rules - Custom security rules (returns {"offset": X, "rules": [...]})
             ContentTypeDefinition {
                 name: "rules",
                 get_endpoint: "appsec/v1/rules",
+                write_endpoint: None,
+                delete_endpoint: None,
                 pull_strategy: PullStrategy::OffsetPaginated {
                     offset_param: "offset",
                     limit_param: "limit",
                     page_size: 100,
                 },
-                id_field: "id",
+                id_fields: &[IdentityField::Any("id")],
+                id_fallback: IdFallback::ContentHash { prefix: "object", fields: &["name"] },
+                stable_ids: false,
+                tenant_id_field: None,
+                name_fields: &["name"],
+                excluded_fields: &[],
+                filename_template: "{name}",
                 request_body: None,
                 response_path: Some("rules"),
+                set_paths: &[],
             },
             
             // Repositories - Code repository configurations
             ContentTypeDefinition {
                 name: "repositories",
                 get_endpoint: "appsec/v1/repositories",
+                write_endpoint: None,
+                delete_endpoint: None,
                 pull_strategy: PullStrategy::OffsetPaginated {
                     offset_param: "offset",
                     limit_param: "limit",
                     page_size: 100,
                 },
-                id_field: "id",
+                id_fields: &[IdentityField::Any("id")],
+                id_fallback: IdFallback::ContentHash { prefix: "object", fields: &["name"] },
+                stable_ids: false,
+                tenant_id_field: None,
+                name_fields: &["name"],
+                excluded_fields: &[],
+                filename_template: "{name}",
                 request_body: None,
                 response_path: None,
+                set_paths: &[],
             },
             
             // Integrations - External data source integrations (returns array at root)
             ContentTypeDefinition {
                 name: "integrations",
                 get_endpoint: "appsec/v1/integrations",
+                write_endpoint: None,
+                delete_endpoint: None,
                 pull_strategy: PullStrategy::JsonCollection,
-                id_field: "id",
+                id_fields: &[IdentityField::Any("id")],
+                id_fallback: IdFallback::ContentHash { prefix: "object", fields: &["name"] },
+                stable_ids: false,
+                tenant_id_field: None,
+                name_fields: &["name"],
+                excluded_fields: &[],
+                filename_template: "{name}",
                 request_body: None,
                 response_path: None,
+                set_paths: &[],
             },
             
             // Application configuration - Singleton configuration endpoint
             ContentTypeDefinition {
                 name: "application_configuration",
                 get_endpoint: "appsec/v1/application/configuration",
+                write_endpoint: None,
+                delete_endpoint: None,
                 pull_strategy: PullStrategy::JsonCollection,
-                id_field: "id",
+                id_fields: &[IdentityField::Any("id")],
+                id_fallback: IdFallback::Fixed("application_configuration"),
+                stable_ids: false,
+                tenant_id_field: None,
+                name_fields: &["name"],
+                excluded_fields: &[],
+                filename_template: "{name}",
                 request_body: None,
                 response_path: None,
+                set_paths: &[],
             },
             
             // Application criteria - Filtering criteria for applications
             ContentTypeDefinition {
                 name: "application_criteria",
                 get_endpoint: "appsec/v1/application/criteria/all",
+                write_endpoint: None,
+                delete_endpoint: None,
                 pull_strategy: PullStrategy::Paginated {
                     page_param: "page",
                     page_size_param: "pageSize",
                     page_size: 100,
                 },
-                id_field: "id",
+                id_fields: &[IdentityField::Any("id")],
+                id_fallback: IdFallback::ContentHash { prefix: "criteria", fields: &["name"] },
+                stable_ids: false,
+                tenant_id_field: None,
+                name_fields: &["name"],
+                excluded_fields: &["createdAt", "lastUpdated", "deletedAt"],
+                filename_template: "{name}",
                 request_body: None,
                 response_path: Some("items"),
+                set_paths: &[],
             },
             
         ]
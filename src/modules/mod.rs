@@ -7,6 +7,8 @@
 use serde_json::Value;
 use std::collections::HashMap;
 
+use crate::types::XsiamObject;
+
 // Module implementations
 mod xsiam;
 mod appsec;
@@ -47,12 +49,38 @@ pub struct ContentTypeDefinition {
     
     /// API endpoint for retrieving items (relative to base_api_path)
     pub get_endpoint: &'static str,
+
+    /// API endpoint for creating or updating items, if the platform offers one
+    pub write_endpoint: Option<&'static str>,
+
+    /// API endpoint for deleting items, if the platform offers one
+    pub delete_endpoint: Option<&'static str>,
     
     /// Pull strategy to use for this content type
     pub pull_strategy: PullStrategy,
     
-    /// Field name for unique ID in API responses
-    pub id_field: &'static str,
+    /// Fields the object ID is read from, in order of preference
+    /// The first is the field used to look objects up by ID in API requests.
+    pub id_fields: &'static [IdentityField],
+
+    /// ID used when the platform gives none of the ID fields
+    pub id_fallback: IdFallback,
+
+    /// Whether IDs are chosen by users rather than assigned by the tenant, so they match across instances
+    pub stable_ids: bool,
+
+    /// Field naming the tenant an object belongs to, stored as tenant_id rather than in the content
+    pub tenant_id_field: Option<IdentityField>,
+
+    /// String fields the object name is read from, in order of preference
+    pub name_fields: &'static [&'static str],
+
+    /// API fields left out of the stored object, besides id, name, description and metadata
+    pub excluded_fields: &'static [&'static str],
+
+    /// File name of an object without the .yaml extension, using {name} and {id}
+    /// Objects without a name are written as <content type>_id_<id>.
+    pub filename_template: &'static str,
     
     /// Optional: Request body for POST endpoints
     pub request_body: Option<Value>,
@@ -60,6 +88,104 @@ pub struct ContentTypeDefinition {
     /// Optional: Response path to extract items from JSON
     /// Examples: "reply", "objects[0].dashboards_data", "data"
     pub response_path: Option<&'static str>,
+
+    /// Array fields whose order carries no meaning, sorted so platform reordering is not a change
+    /// Dot-separated paths into the object, where * matches any key; all other arrays keep their order.
+    pub set_paths: &'static [&'static str],
+}
+
+impl ContentTypeDefinition {
+    /// Field used to look objects up by ID in API requests
+    pub fn id_field(&self) -> &'static str {
+        self.id_fields.first().map(IdentityField::key).unwrap_or("id")
+    }
+
+    /// Read the object ID from an API response item, if any ID field is present
+    pub fn read_id(&self, json: &Value) -> Option<String> {
+        self.id_fields.iter().find_map(|field| field.read(json))
+    }
+
+    /// Read the object name from an API response item
+    pub fn read_name(&self, json: &Value) -> Option<String> {
+        self.name_fields.iter()
+            .find_map(|field| json.get(*field).and_then(Value::as_str))
+            .map(str::to_string)
+    }
+
+    /// Whether an API field is left out of the stored object
+    pub fn is_excluded(&self, field: &str) -> bool {
+        matches!(field, "id" | "name" | "description" | "metadata")
+            || self.excluded_fields.contains(&field)
+            || self.tenant_id_field.is_some_and(|tenant_field| tenant_field.key() == field)
+    }
+
+    /// Content fields holding IDs assigned by the tenant, which differ between instances
+    /// These are the stored ID fields of content types without stable IDs; the top-level id
+    /// is always tenant-specific.
+    pub fn tenant_specific_id_fields(&self) -> Vec<&'static str> {
+        if self.stable_ids {
+            return Vec::new();
+        }
+        self.id_fields.iter()
+            .map(IdentityField::key)
+            .filter(|field| !self.is_excluded(field))
+            .collect()
+    }
+
+    /// File name of an object without the .yaml extension, before collisions are resolved
+    pub fn file_stem(&self, object: &XsiamObject) -> String {
+        let name = object.name.as_deref().filter(|name| !name.trim().is_empty());
+        match name {
+            None if self.filename_template.contains("{name}") => format!("{}_id_{}", self.name.trim_end_matches('s'), object.id),
+            _ => self.filename_template
+                .replace("{name}", &name.unwrap_or_default().replace([' ', '/', '\\'], "_"))
+                .replace("{id}", &object.id),
+        }
+    }
+}
+
+/// A field an identity value is read from, with the JSON types accepted
+#[derive(Debug, Clone, Copy)]
+pub enum IdentityField {
+    /// String values only
+    Str(&'static str),
+    /// Integer values only
+    Int(&'static str),
+    /// String or integer values
+    Any(&'static str),
+}
+
+impl IdentityField {
+    pub fn key(&self) -> &'static str {
+        match self {
+            Self::Str(key) | Self::Int(key) | Self::Any(key) => key,
+        }
+    }
+
+    /// Read the field as a string, if present with an accepted type
+    pub fn read(&self, json: &Value) -> Option<String> {
+        let value = json.get(self.key())?;
+        let as_str = || value.as_str().map(str::to_string);
+        let as_int = || value.as_i64().map(|i| i.to_string());
+        match self {
+            Self::Str(_) => as_str(),
+            Self::Int(_) => as_int(),
+            Self::Any(_) => as_str().or_else(as_int),
+        }
+    }
+}
+
+/// ID given to an object when none of its ID fields is present
+#[derive(Debug, Clone, Copy)]
+pub enum IdFallback {
+    /// Hash of identity fields with a prefix, e.g. widget_3f2a9c1e0b7d
+    /// The whole content without timestamps is hashed if none of the fields is present.
+    ContentHash {
+        prefix: &'static str,
+        fields: &'static [&'static str],
+    },
+    /// Fixed ID, for singleton content types
+    Fixed(&'static str),
 }
 
 /// Pull strategy defines how to retrieve content from APIs
@@ -137,6 +263,23 @@ impl ModuleRegistry {
     pub fn all_modules(&self) -> Vec<&dyn Module> {
         self.modules.values().map(|m| m.as_ref()).collect()
     }
+
+    /// Unordered array paths of every content type, keyed by content type name
+    pub fn set_paths(&self) -> HashMap<&'static str, &'static [&'static str]> {
+        self.modules.values()
+            .flat_map(|module| module.content_types())
+            .map(|content_def| (content_def.name, content_def.set_paths))
+            .collect()
+    }
+}
+
+/// Definition of a content type by module and name, for tests
+#[cfg(test)]
+pub fn content_type_definition(module_id: &str, name: &str) -> ContentTypeDefinition {
+    ModuleRegistry::load().get(module_id).unwrap().content_types()
+        .into_iter()
+        .find(|content_def| content_def.name == name)
+        .unwrap()
 }
 
 #[cfg(test)]
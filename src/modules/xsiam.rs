@@ -5,7 +5,7 @@
 // Supports 9 content types: scripts, dashboards, biocs, correlation_searches, widgets,
 // authentication_settings, scheduled_queries, xql_library, rbac_users
 
-use super::{Module, ContentTypeDefinition, PullStrategy};
+use super::{Module, ContentTypeDefinition, IdFallback, IdentityField, PullStrategy};
 use serde_json::json;
 
 pub struct XsiamModule;
@@ -29,50 +29,95 @@ impl Module for XsiamModule {
             ContentTypeDefinition {
                 name: "dashboards",
                 get_endpoint: "dashboards/get",
+                write_endpoint: None,
+                delete_endpoint: None,
                 pull_strategy: PullStrategy::JsonCollection,
-                id_field: "global_id",
+                id_fields: &[IdentityField::Str("global_id"), IdentityField::Int("default_dashboard_id"), IdentityField::Str("dashboard_id"), IdentityField::Str("id")],
+                id_fallback: IdFallback::ContentHash { prefix: "dashboard", fields: &["name"] },
+                stable_ids: false,
+                tenant_id_field: None,
+                name_fields: &["name"],
+                excluded_fields: &[],
+                filename_template: "{name}",
                 request_body: Some(json!({"request_data": {}})),
                 response_path: Some("objects[0].dashboards_data"),
+                set_paths: &[],
             },
             
             // BIOCs (Behavioural Indicators of Compromise) - Simple JSON collection
             ContentTypeDefinition {
                 name: "biocs",
                 get_endpoint: "bioc/get",
+                write_endpoint: Some("bioc/insert"),
+                delete_endpoint: Some("bioc/delete"),
                 pull_strategy: PullStrategy::JsonCollection,
-                id_field: "rule_id",
+                id_fields: &[IdentityField::Int("rule_id"), IdentityField::Str("id")],
+                id_fallback: IdFallback::ContentHash { prefix: "rule", fields: &["name"] },
+                stable_ids: false,
+                tenant_id_field: None,
+                name_fields: &["name"],
+                excluded_fields: &[],
+                filename_template: "{name}",
                 request_body: Some(json!({"request_data": {"extended_view": true}})),
                 response_path: Some("objects"),
+                set_paths: &["mitre_tactic_id_and_name", "mitre_technique_id_and_name"],
             },
             
             // Correlation searches - Security correlation rules
             ContentTypeDefinition {
                 name: "correlation_searches",
                 get_endpoint: "correlations/get",
+                write_endpoint: Some("correlations/insert"),
+                delete_endpoint: Some("correlations/delete"),
                 pull_strategy: PullStrategy::JsonCollection,
-                id_field: "rule_id",
+                id_fields: &[IdentityField::Int("rule_id"), IdentityField::Str("id")],
+                id_fallback: IdFallback::ContentHash { prefix: "rule", fields: &["name"] },
+                stable_ids: false,
+                tenant_id_field: None,
+                name_fields: &["name"],
+                excluded_fields: &[],
+                filename_template: "{name}",
                 request_body: Some(json!({"request_data": {"extended_view": true}})),
                 response_path: Some("objects"),
+                set_paths: &["mitre_defs.*"],
             },
             
             // Widgets - Dashboard widgets
             ContentTypeDefinition {
                 name: "widgets",
                 get_endpoint: "widgets/get",
+                write_endpoint: None,
+                delete_endpoint: None,
                 pull_strategy: PullStrategy::JsonCollection,
-                id_field: "creation_time",
+                id_fields: &[IdentityField::Int("creation_time"), IdentityField::Str("global_id"), IdentityField::Str("widget_id"), IdentityField::Str("id")],
+                id_fallback: IdFallback::ContentHash { prefix: "widget", fields: &["title", "type"] },
+                stable_ids: false,
+                tenant_id_field: None,
+                name_fields: &["title", "name", "widget_name"],
+                excluded_fields: &[],
+                filename_template: "{name}",
                 request_body: Some(json!({"request_data": {}})),
                 response_path: Some("objects[0].widgets_data"),
+                set_paths: &[],
             },
             
             // Authentication settings - SSO and authentication configurations
             ContentTypeDefinition {
                 name: "authentication_settings",
                 get_endpoint: "authentication-settings/get/settings",
+                write_endpoint: None,
+                delete_endpoint: None,
                 pull_strategy: PullStrategy::JsonCollection,
-                id_field: "name",
+                id_fields: &[IdentityField::Str("name"), IdentityField::Str("setting_name"), IdentityField::Str("type")],
+                id_fallback: IdFallback::ContentHash { prefix: "auth_setting", fields: &["name", "setting_name", "type"] },
+                stable_ids: true,
+                tenant_id_field: Some(IdentityField::Any("tenant_id")),
+                name_fields: &["name", "setting_name", "type"],
+                excluded_fields: &[],
+                filename_template: "{name}",
                 request_body: Some(json!({"request_data": {}})),
                 response_path: Some("reply"),
+                set_paths: &[],
             },
             
             // Scripts - Two-step code retrieval via script_uid
@@ -81,25 +126,43 @@ impl Module for XsiamModule {
             ContentTypeDefinition {
                 name: "scripts",
                 get_endpoint: "scripts/get_scripts",
+                write_endpoint: None,
+                delete_endpoint: None,
                 pull_strategy: PullStrategy::ScriptCode {
                     list_endpoint: "scripts/get_scripts",
                     code_endpoint: "scripts/get_script_code",
                     list_response_path: "reply.scripts",
                     uid_field: "script_uid",
                 },
-                id_field: "script_uid",
+                id_fields: &[IdentityField::Str("script_uid")],
+                id_fallback: IdFallback::ContentHash { prefix: "object", fields: &["name"] },
+                stable_ids: false,
+                tenant_id_field: None,
+                name_fields: &["name"],
+                excluded_fields: &[],
+                filename_template: "{name}",
                 request_body: Some(json!({"request_data": {}})),
                 response_path: None,
+                set_paths: &["tags"],
             },
             
             // Scheduled queries - XQL scheduled queries
             ContentTypeDefinition {
                 name: "scheduled_queries",
                 get_endpoint: "scheduled_queries/list",
+                write_endpoint: None,
+                delete_endpoint: None,
                 pull_strategy: PullStrategy::JsonCollection,
-                id_field: "query_def_id",
+                id_fields: &[IdentityField::Any("query_def_id")],
+                id_fallback: IdFallback::ContentHash { prefix: "scheduled_query", fields: &["name"] },
+                stable_ids: false,
+                tenant_id_field: None,
+                name_fields: &["name"],
+                excluded_fields: &[],
+                filename_template: "{name}",
                 request_body: Some(json!({"request_data": {"extended_view": true}})),
                 response_path: Some("reply.DATA"),
+                set_paths: &[],
             },
             
             // XQL Library - Reusable XQL query library
@@ -107,10 +170,19 @@ impl Module for XsiamModule {
             ContentTypeDefinition {
                 name: "xql_library",
                 get_endpoint: "../xql_library/get",
+                write_endpoint: None,
+                delete_endpoint: None,
                 pull_strategy: PullStrategy::JsonCollection,
-                id_field: "id",
+                id_fields: &[IdentityField::Any("id")],
+                id_fallback: IdFallback::ContentHash { prefix: "xql", fields: &["name"] },
+                stable_ids: false,
+                tenant_id_field: None,
+                name_fields: &["name"],
+                excluded_fields: &[],
+                filename_template: "{name}",
                 request_body: Some(json!({"request_data": {"extended_view": true}})),
                 response_path: Some("reply.xql_queries"),
+                set_paths: &["tags"],
             },
             
             
@@ -118,10 +190,19 @@ impl Module for XsiamModule {
             ContentTypeDefinition {
                 name: "rbac_users",
                 get_endpoint: "rbac/get_users",
+                write_endpoint: None,
+                delete_endpoint: None,
                 pull_strategy: PullStrategy::JsonCollection,
-                id_field: "user_email",
+                id_fields: &[IdentityField::Str("user_email")],
+                id_fallback: IdFallback::ContentHash { prefix: "user", fields: &["user_email"] },
+                stable_ids: true,
+                tenant_id_field: None,
+                name_fields: &["user_email"],
+                excluded_fields: &["last_logged_in"],
+                filename_template: "{name}",
                 request_body: Some(json!({"request_data": {}})),
                 response_path: Some("reply"),
+                set_paths: &["groups", "role_name"],
             },
         ]
     }
@@ -174,4 +255,50 @@ mod tests {
             _ => panic!("Scripts should use ScriptCode pull strategy"),
         }
     }
+    
+    #[test]
+    fn test_identity_rules() {
+        let module = XsiamModule;
+        let types = module.content_types();
+        let content_def = |name: &str| types.iter().find(|t| t.name == name).unwrap();
+        
+        // Widgets prefer the numeric creation time, then the global ID, and are named by title
+        let widget = json!({"creation_time": "not a number", "global_id": "g-1", "title": "Alerts", "name": "alerts"});
+        assert_eq!(content_def("widgets").read_id(&widget), Some("g-1".to_string()));
+        assert_eq!(content_def("widgets").read_name(&widget), Some("Alerts".to_string()));
+        assert_eq!(content_def("widgets").id_field(), "creation_time");
+        
+        // Users are identified and named by email, without their login time
+        let users = content_def("rbac_users");
+        let user = json!({"user_email": "jane@example.com", "last_logged_in": 1700000000});
+        assert_eq!(users.read_id(&user), Some("jane@example.com".to_string()));
+        assert!(users.is_excluded("last_logged_in"));
+        assert!(!content_def("biocs").is_excluded("last_logged_in"));
+        
+        // Tenant-assigned ID fields follow the ID fields; stable IDs have none
+        assert_eq!(content_def("widgets").tenant_specific_id_fields(), vec!["creation_time", "global_id", "widget_id"]);
+        assert_eq!(content_def("xql_library").tenant_specific_id_fields(), Vec::<&str>::new());
+        assert_eq!(users.tenant_specific_id_fields(), Vec::<&str>::new());
+        
+        // Authentication settings keep their tenant apart from the content
+        let settings = content_def("authentication_settings");
+        let setting = json!({"name": "SSO", "tenant_id": 1234});
+        let object = crate::types::XsiamObject::from_api_response(&setting, settings).unwrap();
+        assert_eq!(object.tenant_id.as_deref(), Some("1234"));
+        assert!(!object.content.contains_key("tenant_id"));
+    }
+    
+    #[test]
+    fn test_file_stem() {
+        let module = XsiamModule;
+        let types = module.content_types();
+        let biocs = types.iter().find(|t| t.name == "biocs").unwrap();
+        
+        let named = crate::types::XsiamObject::new("7".to_string(), "Suspicious Login/Admin".to_string(), "biocs".to_string());
+        assert_eq!(biocs.file_stem(&named), "Suspicious_Login_Admin");
+        
+        let mut unnamed = named.clone();
+        unnamed.name = Some("  ".to_string());
+        assert_eq!(biocs.file_stem(&unnamed), "bioc_id_7");
+    }
 }
@@ -0,0 +1,201 @@
+// SPDX-FileCopyrightText: GoCortexIO
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+// Normalisation of volatile fields
+// Fields that change on every pull, such as hit counters and trigger times, are dropped,
+// zeroed or moved to metadata before objects are written or compared, following the
+// [normalise.<content_type>] blocks of config.toml.
+
+use anyhow::Result;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+use crate::config::{LayoutSettings, NormaliseRules};
+use crate::sidecar;
+use crate::types::XsiamObject;
+
+/// Per content type normalisation rules for an instance
+#[derive(Debug, Default, Clone)]
+pub struct Normaliser {
+    rules: BTreeMap<String, NormaliseRules>,
+}
+
+impl Normaliser {
+    pub fn new(rules: BTreeMap<String, NormaliseRules>) -> Self {
+        Self { rules }
+    }
+
+    /// Check the rules against the file layout
+    /// Fields moved to metadata only stay out of the object files with the metadata index;
+    /// otherwise every change to them would still rewrite the file and create a pull commit.
+    pub fn check_layout(&self, layout: &LayoutSettings) -> Result<()> {
+        if layout.metadata_index {
+            return Ok(());
+        }
+        match self.rules.iter().find(|(_, rules)| !rules.metadata.is_empty()) {
+            Some((content_type, _)) => Err(anyhow::anyhow!(
+                "[normalise.{content_type}] moves fields to metadata, which needs metadata_index = true under [layout]; use drop or zero instead to keep the default layout"
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Normalise an object's volatile fields
+    /// Fields are dot-separated paths into the content; a path through a list applies to every item.
+    /// Code and XQL fields are kept byte for byte, so rules naming them are ignored.
+    pub fn apply(&self, object: &XsiamObject) -> XsiamObject {
+        let mut normalised = object.clone();
+        let Some(rules) = self.rules.get(&object.content_type) else {
+            return normalised;
+        };
+        let verbatim = sidecar::sidecar_fields(&object.content_type);
+        let applies = |path: &&String| !verbatim.iter().any(|field| path.split('.').next() == Some(*field));
+
+        for path in rules.drop.iter().filter(applies) {
+            take_field(&mut normalised.content, path);
+        }
+        for path in rules.zero.iter().filter(applies) {
+            let segments: Vec<&str> = path.split('.').collect();
+            if let Some(value) = normalised.content.get_mut(segments[0]) {
+                visit(value, &segments[1..], &mut |value| *value = zero_of(value));
+            }
+        }
+        // Metadata is excluded from comparisons and kept in the metadata index, so moved fields
+        // neither show in diffs nor touch the object file
+        for path in rules.metadata.iter().filter(applies) {
+            if let Some(value) = take_field(&mut normalised.content, path) {
+                normalised.metadata.additional.insert(path.clone(), value);
+            }
+        }
+
+        normalised
+    }
+}
+
+/// Remove the field at a path, returning its value (a list of values for paths through lists)
+fn take_field(content: &mut std::collections::HashMap<String, Value>, path: &str) -> Option<Value> {
+    let segments: Vec<&str> = path.split('.').collect();
+    let (first, rest) = segments.split_first()?;
+    if rest.is_empty() {
+        return content.remove(*first);
+    }
+    let mut taken = Vec::new();
+    let (parents, last) = rest.split_at(rest.len() - 1);
+    visit(content.get_mut(*first)?, parents, &mut |value| {
+        if let Some(removed) = value.as_object_mut().and_then(|map| map.remove(last[0])) {
+            taken.push(removed);
+        }
+    });
+    match taken.len() {
+        0 => None,
+        1 => taken.pop(),
+        _ => Some(Value::Array(taken)),
+    }
+}
+
+/// Call `action` on every value a path leads to
+fn visit(value: &mut Value, path: &[&str], action: &mut dyn FnMut(&mut Value)) {
+    let Some((first, rest)) = path.split_first() else {
+        action(value);
+        return;
+    };
+    match value {
+        Value::Array(items) => {
+            for item in items {
+                visit(item, path, action);
+            }
+        }
+        Value::Object(map) => {
+            if let Some(child) = map.get_mut(*first) {
+                visit(child, rest, action);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Empty value of the same type
+fn zero_of(value: &Value) -> Value {
+    match value {
+        Value::Number(_) => Value::from(0),
+        Value::String(_) => Value::String(String::new()),
+        Value::Bool(_) => Value::Bool(false),
+        Value::Array(_) => Value::Array(Vec::new()),
+        Value::Object(_) => Value::Object(serde_json::Map::new()),
+        Value::Null => Value::Null,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn normaliser() -> Normaliser {
+        let rules: BTreeMap<String, NormaliseRules> = toml::from_str(
+            "[biocs]\n\
+             drop = [\"hit_count\", \"stats.last_seen\"]\n\
+             zero = [\"lastTriggered\", \"actions.run_count\"]\n\
+             metadata = [\"modification_time\"]\n"
+        ).unwrap();
+        Normaliser::new(rules)
+    }
+
+    #[test]
+    fn test_drop_zero_and_move_to_metadata() {
+        let mut rule = XsiamObject::new("1".to_string(), "Rule".to_string(), "biocs".to_string());
+        rule.content.insert("hit_count".to_string(), json!(42));
+        rule.content.insert("stats".to_string(), json!({"last_seen": 1700000000, "enabled": true}));
+        rule.content.insert("lastTriggered".to_string(), json!("2026-10-01T12:00:00Z"));
+        rule.content.insert("actions".to_string(), json!([{"type": "alert", "run_count": 3}, {"type": "email", "run_count": 9}]));
+        rule.content.insert("modification_time".to_string(), json!(1700000500));
+
+        let normalised = normaliser().apply(&rule);
+        assert!(!normalised.content.contains_key("hit_count"));
+        assert_eq!(normalised.content["stats"], json!({"enabled": true}));
+        assert_eq!(normalised.content["lastTriggered"], json!(""));
+        assert_eq!(normalised.content["actions"], json!([{"type": "alert", "run_count": 0}, {"type": "email", "run_count": 0}]));
+        assert!(!normalised.content.contains_key("modification_time"));
+        assert_eq!(normalised.metadata.additional["modification_time"], json!(1700000500));
+
+        // Normalising again changes nothing
+        assert_eq!(normaliser().apply(&normalised), normalised);
+    }
+
+    #[test]
+    fn test_moving_to_metadata_needs_metadata_index() {
+        let rules: BTreeMap<String, NormaliseRules> = toml::from_str(
+            "[biocs]\nmetadata = [\"modification_time\"]\n[widgets]\ndrop = [\"hits\"]"
+        ).unwrap();
+        let normaliser = Normaliser::new(rules);
+
+        let error = normaliser.check_layout(&LayoutSettings::default()).unwrap_err();
+        assert!(error.to_string().contains("[normalise.biocs]"));
+        assert!(normaliser.check_layout(&LayoutSettings { metadata_index: true, ..LayoutSettings::default() }).is_ok());
+
+        // Dropping and zeroing work with any layout
+        let rules: BTreeMap<String, NormaliseRules> = toml::from_str("[widgets]\ndrop = [\"hits\"]").unwrap();
+        assert!(Normaliser::new(rules).check_layout(&LayoutSettings::default()).is_ok());
+    }
+
+    #[test]
+    fn test_script_code_is_never_normalised() {
+        let rules: BTreeMap<String, NormaliseRules> = toml::from_str(
+            "[scripts]\nzero = [\"code\", \"run_count\"]\n"
+        ).unwrap();
+        let mut script = XsiamObject::new("2".to_string(), "Script".to_string(), "scripts".to_string());
+        script.content.insert("code".to_string(), json!("return 1\n"));
+        script.content.insert("run_count".to_string(), json!(7));
+
+        let normalised = Normaliser::new(rules).apply(&script);
+        assert_eq!(normalised.content["code"], json!("return 1\n"));
+        assert_eq!(normalised.content["run_count"], json!(0));
+    }
+
+    #[test]
+    fn test_other_content_types_untouched() {
+        let mut script = XsiamObject::new("2".to_string(), "Script".to_string(), "scripts".to_string());
+        script.content.insert("hit_count".to_string(), json!(42));
+        assert_eq!(normaliser().apply(&script), script);
+    }
+}
@@ -0,0 +1,76 @@
+// SPDX-FileCopyrightText: GoCortexIO
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+// Structured field-level differences between two versions of an object
+// Metadata is excluded, matching YamlParser::objects_are_logically_equal
+
+use serde_json::Value;
+use std::collections::BTreeSet;
+
+use crate::types::XsiamObject;
+
+/// A single changed field, addressed by a dotted path
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldChange {
+    pub path: String,
+    /// Value before the change; None if the field was added
+    pub old: Option<Value>,
+    /// Value after the change; None if the field was removed
+    pub new: Option<Value>,
+}
+
+/// List the fields that differ between two versions of an object
+/// Nested maps are compared key by key; lists and scalars are compared as a whole.
+pub fn field_changes(old: &XsiamObject, new: &XsiamObject) -> Vec<FieldChange> {
+    let mut changes = Vec::new();
+    diff_values(Some(&old.fields()), Some(&new.fields()), "", &mut changes);
+    changes
+}
+
+fn diff_values(old: Option<&Value>, new: Option<&Value>, path: &str, changes: &mut Vec<FieldChange>) {
+    if old == new {
+        return;
+    }
+
+    if let (Some(Value::Object(old_map)), Some(Value::Object(new_map))) = (old, new) {
+        let keys: BTreeSet<&String> = old_map.keys().chain(new_map.keys()).collect();
+        for key in keys {
+            let child_path = if path.is_empty() { key.clone() } else { format!("{path}.{key}") };
+            diff_values(old_map.get(key), new_map.get(key), &child_path, changes);
+        }
+        return;
+    }
+
+    changes.push(FieldChange {
+        path: path.to_string(),
+        old: old.cloned(),
+        new: new.cloned(),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_field_changes() {
+        let mut old = XsiamObject::new("1".to_string(), "Rule".to_string(), "biocs".to_string());
+        old.content.insert("severity".to_string(), json!("low"));
+        old.content.insert("schedule".to_string(), json!({"interval": 10, "timezone": "UTC"}));
+        old.content.insert("retired".to_string(), json!(false));
+
+        let mut new = old.clone();
+        new.description = "Updated".to_string();
+        new.content.insert("severity".to_string(), json!("high"));
+        new.content.insert("schedule".to_string(), json!({"interval": 5, "timezone": "UTC"}));
+        new.content.remove("retired");
+        new.metadata.version = "2".to_string();
+
+        let changes = field_changes(&old, &new);
+        let paths: Vec<&str> = changes.iter().map(|c| c.path.as_str()).collect();
+        assert_eq!(paths, vec!["description", "retired", "schedule.interval", "severity"]);
+        assert_eq!(changes[1].new, None);
+        assert_eq!(changes[3].old, Some(json!("low")));
+    }
+}
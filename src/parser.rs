@@ -2,23 +2,78 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
 use anyhow::{Result, Context};
+use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
 
-use crate::types::XsiamObject;
-
-pub struct YamlParser;
+use crate::block_scalars;
+use crate::config::LayoutSettings;
+use crate::metadata_index;
+use crate::modules::ModuleRegistry;
+use crate::sidecar;
+use crate::types::{ObjectMetadata, XsiamObject};
+
+pub struct YamlParser {
+    layout: LayoutSettings,
+    metadata_indexes: metadata_index::IndexCache,
+    /// Unordered array paths per content type, read from the module definitions once
+    set_paths: HashMap<&'static str, &'static [&'static str]>,
+}
 
 impl YamlParser {
     pub fn new() -> Self {
-        Self
+        Self::with_layout(LayoutSettings::default())
+    }
+
+    /// Parser that writes objects using an instance's [layout] settings
+    pub fn with_layout(layout: LayoutSettings) -> Self {
+        Self {
+            layout,
+            metadata_indexes: metadata_index::IndexCache::default(),
+            set_paths: ModuleRegistry::load().set_paths(),
+        }
+    }
+
+    /// Unordered array paths declared for a content type
+    fn set_paths(&self, content_type: &str) -> &'static [&'static str] {
+        self.set_paths.get(content_type).copied().unwrap_or(&[])
+    }
+
+    /// Whether object metadata is written to the metadata index instead of the object files
+    pub fn uses_metadata_index(&self) -> bool {
+        self.layout.metadata_index
     }
 
     pub fn parse_file(&self, file_path: &str) -> Result<XsiamObject> {
         let content = fs::read_to_string(file_path)
             .with_context(|| format!("Failed to read file: {file_path}"))?;
 
-        let mut object: XsiamObject = serde_yaml_ng::from_str(&content)
+        let mut object = self.parse_content(&content, file_path)?;
+
+        // Objects written without metadata take it from the content type's metadata index
+        if object.metadata == ObjectMetadata::default() {
+            if let Some(metadata) = self.metadata_indexes.lookup(file_path)? {
+                object.metadata = metadata;
+            }
+        }
+
+        // Read fields stored in sidecar files next to the YAML
+        let dir = Path::new(file_path).parent().unwrap_or(Path::new(""));
+        sidecar::join(object, |file_name| {
+            let path = dir.join(file_name);
+            if !path.exists() {
+                return Ok(None);
+            }
+            fs::read_to_string(&path)
+                .map(Some)
+                .with_context(|| format!("Failed to read sidecar file: {}", path.display()))
+        }).with_context(|| format!("Failed to load {file_path}"))
+    }
+
+    /// Parse YAML that did not come from the worktree (e.g. a file read from Git history)
+    /// The path is used for content type inference and error messages only.
+    pub fn parse_content(&self, content: &str, file_path: &str) -> Result<XsiamObject> {
+        let mut object: XsiamObject = serde_yaml_ng::from_str(content)
             .with_context(|| format!("Failed to parse YAML file: {file_path}"))?;
 
         // Infer content type from file path if not specified
@@ -32,21 +87,38 @@ impl YamlParser {
         Ok(object)
     }
 
-    pub fn write_file(&self, file_path: &str, object: &XsiamObject) -> Result<()> {
+    /// Write an object to its YAML file and any sidecar files, returning every path written
+    pub fn write_file(&self, file_path: &str, object: &XsiamObject) -> Result<Vec<String>> {
         // Ensure directory exists
         if let Some(parent) = Path::new(file_path).parent() {
             fs::create_dir_all(parent)
                 .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
         }
 
+        let mut written = Vec::new();
+        for (path, content) in self.object_files(object, file_path)? {
+            fs::write(&path, content)
+                .with_context(|| format!("Failed to write file: {path}"))?;
+            written.push(path);
+        }
+
+        Ok(written)
+    }
+
+    /// Files that store an object: the YAML at the given path followed by any sidecar files next to it
+    pub fn object_files(&self, object: &XsiamObject, yaml_path: &str) -> Result<Vec<(String, Vec<u8>)>> {
+        let (stripped, sidecars) = sidecar::split(object, yaml_path, &self.layout);
+
         // Create a deterministic YAML output with consistent field ordering
-        let yaml_content = self.serialize_object_deterministically(object)
+        let yaml_content = self.serialize_object_deterministically(&stripped)
             .with_context(|| "Failed to serialize object to YAML".to_string())?;
 
-        fs::write(file_path, yaml_content)
-            .with_context(|| format!("Failed to write file: {file_path}"))?;
-
-        Ok(())
+        let dir = yaml_path.rsplit_once('/').map(|(dir, _)| format!("{dir}/")).unwrap_or_default();
+        let mut files = vec![(yaml_path.to_string(), yaml_content.into_bytes())];
+        for (file_name, content) in sidecars {
+            files.push((format!("{dir}{file_name}"), content.into_bytes()));
+        }
+        Ok(files)
     }
 
     pub fn serialize_object_deterministically(&self, object: &XsiamObject) -> Result<String> {
@@ -62,9 +134,11 @@ impl YamlParser {
         yaml_map.insert(YamlValue::String("description".to_string()), YamlValue::String(object.description.clone()));
         yaml_map.insert(YamlValue::String("content_type".to_string()), YamlValue::String(object.content_type.clone()));
         
-        // Serialize metadata with consistent ordering
-        let metadata_yaml = serde_yaml_ng::to_value(&object.metadata)?;
-        yaml_map.insert(YamlValue::String("metadata".to_string()), metadata_yaml);
+        // Serialize metadata with consistent ordering, unless it is kept in the metadata index
+        if !self.layout.metadata_index {
+            let metadata_yaml = serde_yaml_ng::to_value(&object.metadata)?;
+            yaml_map.insert(YamlValue::String("metadata".to_string()), metadata_yaml);
+        }
         
         // Sort content HashMap keys alphabetically for deterministic YAML output
         // Known limitation: If the API changes the order of fields returned, Git will show
@@ -73,13 +147,14 @@ impl YamlParser {
         // This trade-off is acceptable as we prioritise stable version control over mirroring API field order.
         let mut sorted_keys: Vec<_> = object.content.keys().collect();
         sorted_keys.sort();
+        let set_paths = self.set_paths(&object.content_type);
         
         // Add content fields in alphabetical order
         for key in sorted_keys {
             if let Some(value) = object.content.get(key) {
                 let mut json_val = serde_json::to_value(value)
                     .map_err(|e| anyhow::anyhow!("JSON serialisation error: {e}"))?;
-                Self::sort_json_arrays(&mut json_val);
+                Self::sort_set_arrays(&mut json_val, key, set_paths);
                 let yaml_value = serde_yaml_ng::to_value(json_val)
                     .map_err(|e| anyhow::anyhow!("YAML serialisation error: {e}"))
                     .unwrap_or(YamlValue::Null);
@@ -87,8 +162,8 @@ impl YamlParser {
             }
         }
 
-        serde_yaml_ng::to_string(&YamlValue::Mapping(yaml_map))
-            .with_context(|| "Failed to convert to YAML string")
+        // Multi-line strings such as code and XQL are written as literal block scalars
+        block_scalars::to_string(&YamlValue::Mapping(yaml_map))
     }
 
     /// Compare two XsiamObjects using deterministic serialisation to ensure accurate comparison.
@@ -112,14 +187,16 @@ impl YamlParser {
         }
 
         // Compare content using deterministic serialisation
-        let content1_yaml = self.serialize_content_deterministically(&obj1.content)?;
-        let content2_yaml = self.serialize_content_deterministically(&obj2.content)?;
+        // Arrays declared as sets compare equal in any order; all others must match in order
+        let set_paths = self.set_paths(&obj1.content_type);
+        let content1_yaml = self.serialize_content_deterministically(&obj1.content, set_paths)?;
+        let content2_yaml = self.serialize_content_deterministically(&obj2.content, set_paths)?;
         
         Ok(content1_yaml == content2_yaml)
     }
 
     /// Serialize just the content HashMap with deterministic ordering
-    fn serialize_content_deterministically(&self, content: &std::collections::HashMap<String, serde_json::Value>) -> Result<String> {
+    fn serialize_content_deterministically(&self, content: &std::collections::HashMap<String, serde_json::Value>, set_paths: &[&str]) -> Result<String> {
         use serde_yaml_ng::{Mapping, Value as YamlValue};
 
         let mut yaml_map = Mapping::new();
@@ -133,7 +210,7 @@ impl YamlParser {
             if let Some(value) = content.get(key) {
                 let mut json_val = serde_json::to_value(value)
                     .map_err(|e| anyhow::anyhow!("JSON serialisation error: {e}"))?;
-                Self::sort_json_arrays(&mut json_val);
+                Self::sort_set_arrays(&mut json_val, key, set_paths);
                 let yaml_value = serde_yaml_ng::to_value(json_val)
                     .map_err(|e| anyhow::anyhow!("YAML serialisation error: {e}"))
                     .unwrap_or(YamlValue::Null);
@@ -196,28 +273,40 @@ impl YamlParser {
         Err(anyhow::anyhow!("Unable to infer content type from file path: {file_path}"))
     }
 
-    fn sort_json_arrays(value: &mut serde_json::Value) {
+    /// Sort the arrays found at the declared set paths, leaving ordered lists untouched
+    /// `path` is the dot-separated path of `value`; list items share the path of their list.
+    fn sort_set_arrays(value: &mut serde_json::Value, path: &str, set_paths: &[&str]) {
         match value {
             serde_json::Value::Array(arr) => {
-                if arr.iter().all(|v| v.is_string()) {
-                    arr.sort_by(|a, b| a.as_str().unwrap_or("").cmp(b.as_str().unwrap_or("")));
-                } else {
-                    for item in arr.iter_mut() {
-                        Self::sort_json_arrays(item);
-                    }
+                for item in arr.iter_mut() {
+                    Self::sort_set_arrays(item, path, set_paths);
+                }
+                if set_paths.iter().any(|set_path| Self::path_matches(set_path, path)) {
+                    arr.sort_by(|a, b| match (a.as_str(), b.as_str()) {
+                        (Some(a), Some(b)) => a.cmp(b),
+                        _ => a.to_string().cmp(&b.to_string()),
+                    });
                 }
             }
             serde_json::Value::Object(map) => {
-                for (_key, val) in map.iter_mut() {
-                    Self::sort_json_arrays(val);
+                for (key, val) in map.iter_mut() {
+                    Self::sort_set_arrays(val, &format!("{path}.{key}"), set_paths);
                 }
             }
             _ => {}
         }
     }
 
+    /// Match a dot-separated path against a pattern in which * stands for any single key
+    fn path_matches(pattern: &str, path: &str) -> bool {
+        let pattern: Vec<&str> = pattern.split('.').collect();
+        let path: Vec<&str> = path.split('.').collect();
+        pattern.len() == path.len() && pattern.iter().zip(&path).all(|(p, k)| *p == "*" || p == k)
+    }
+
     fn validate_object(&self, object: &XsiamObject) -> Result<()> {
-        if object.id.is_empty() {
+        // Promoted objects new to the target get their ID from the platform on push
+        if object.id.is_empty() && !object.is_promoted() {
             return Err(anyhow::anyhow!("Object ID is required"));
         }
 
@@ -235,3 +324,56 @@ impl YamlParser {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn bioc(tactics: serde_json::Value, fields: serde_json::Value) -> XsiamObject {
+        let mut object = XsiamObject::new("1".to_string(), "Rule".to_string(), "biocs".to_string());
+        object.content.insert("mitre_tactic_id_and_name".to_string(), tactics);
+        object.content.insert("indicator".to_string(), json!({"fields": fields}));
+        object
+    }
+
+    #[test]
+    fn test_only_set_paths_are_sorted() {
+        let parser = YamlParser::new();
+        let object = bioc(json!(["TA0002", "TA0001"]), json!(["host", "action"]));
+
+        let yaml = parser.serialize_object_deterministically(&object).unwrap();
+        assert!(yaml.contains("mitre_tactic_id_and_name:\n- TA0001\n- TA0002\n"));
+        assert!(yaml.contains("  fields:\n  - host\n  - action\n"));
+    }
+
+    #[test]
+    fn test_logical_equality_respects_list_order() {
+        let parser = YamlParser::new();
+        let object = bioc(json!(["TA0001", "TA0002"]), json!(["host", "action"]));
+
+        let reordered_set = bioc(json!(["TA0002", "TA0001"]), json!(["host", "action"]));
+        assert!(parser.objects_are_logically_equal(&object, &reordered_set).unwrap());
+
+        let reordered_list = bioc(json!(["TA0001", "TA0002"]), json!(["action", "host"]));
+        assert!(!parser.objects_are_logically_equal(&object, &reordered_list).unwrap());
+    }
+
+    #[test]
+    fn test_metadata_index_layout_omits_metadata() {
+        let object = bioc(json!([]), json!([]));
+        let layout = LayoutSettings { metadata_index: true, ..LayoutSettings::default() };
+
+        let yaml = YamlParser::with_layout(layout).serialize_object_deterministically(&object).unwrap();
+        assert!(!yaml.contains("metadata:"));
+        let parsed = YamlParser::new().parse_content(&yaml, "xsiam/biocs/Rule.yaml").unwrap();
+        assert_eq!(parsed.metadata, ObjectMetadata::default());
+    }
+
+    #[test]
+    fn test_path_matches_wildcards() {
+        assert!(YamlParser::path_matches("mitre_defs.*", "mitre_defs.TA0001"));
+        assert!(!YamlParser::path_matches("mitre_defs.*", "mitre_defs"));
+        assert!(!YamlParser::path_matches("tags", "labels"));
+    }
+}
@@ -0,0 +1,172 @@
+// SPDX-FileCopyrightText: GoCortexIO
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+// Promotion of objects from one instance to another
+// Tenant-specific IDs are taken from the matching target object, or cleared for new objects
+
+use anyhow::{Context, Result};
+use serde_json::{json, Value};
+use std::path::Path;
+
+use crate::modules::ContentTypeDefinition;
+use crate::parser::YamlParser;
+use crate::types::{ObjectMetadata, XsiamObject, PROMOTED_FROM_KEY};
+
+/// An object file in an instance directory
+pub struct LocalObject {
+    pub path: String,
+    pub object: XsiamObject,
+}
+
+/// Load the objects of one content type from an instance directory
+pub fn load_objects(yaml_parser: &YamlParser, instance_name: &str, module_id: &str, content_type: &str) -> Result<Vec<LocalObject>> {
+    let module_dir = format!("{instance_name}/{module_id}");
+    yaml_parser.get_local_files(&module_dir, &[content_type])?
+        .into_iter()
+        .map(|path| {
+            let object = yaml_parser.parse_file(&path)?;
+            Ok(LocalObject { path, object })
+        })
+        .collect()
+}
+
+/// Pick the objects named in the selection by ID or name
+/// Every selector must match at least one object.
+pub fn select_objects<'a>(objects: &'a [LocalObject], selectors: &[String]) -> Result<Vec<&'a LocalObject>> {
+    let mut selected: Vec<&LocalObject> = Vec::new();
+    for selector in selectors {
+        let matches: Vec<&LocalObject> = objects.iter()
+            .filter(|local| local.object.id == *selector || local.object.name.as_deref() == Some(selector.as_str()))
+            .collect();
+        if matches.is_empty() {
+            return Err(anyhow::anyhow!("No object with ID or name '{selector}'"));
+        }
+        for local in matches {
+            if !selected.iter().any(|existing| existing.path == local.path) {
+                selected.push(local);
+            }
+        }
+    }
+    Ok(selected)
+}
+
+/// Find the target object a promoted object should replace, matching by name
+/// IDs are not used since tenants assign their own.
+pub fn find_counterpart<'a>(source: &XsiamObject, targets: &'a [LocalObject]) -> Option<&'a LocalObject> {
+    let name = source.name.as_ref()?;
+    targets.iter().find(|local| local.object.name.as_ref() == Some(name))
+}
+
+/// Build the object to write on the target instance
+/// IDs, the tenant ID and metadata come from the target counterpart when there is one;
+/// otherwise they are left empty so the target platform assigns new ones on push.
+pub fn remap_for_target(content_def: &ContentTypeDefinition, source: &XsiamObject, counterpart: Option<&XsiamObject>, source_instance: &str, source_commit: Option<&str>) -> XsiamObject {
+    let mut promoted = source.clone();
+    let id_fields = content_def.tenant_specific_id_fields();
+
+    match counterpart {
+        Some(target) => {
+            promoted.id = target.id.clone();
+            promoted.tenant_id = target.tenant_id.clone();
+            promoted.metadata = target.metadata.clone();
+            for field in id_fields {
+                match target.content.get(field) {
+                    Some(value) => promoted.content.insert(field.to_string(), value.clone()),
+                    None => promoted.content.remove(field),
+                };
+            }
+        }
+        None => {
+            promoted.id = String::new();
+            promoted.tenant_id = None;
+            promoted.metadata = ObjectMetadata::default();
+            for field in id_fields {
+                promoted.content.remove(field);
+            }
+        }
+    }
+
+    let mut provenance = json!({"instance": source_instance, "id": source.id});
+    if let Some(commit) = source_commit {
+        provenance["commit"] = Value::String(commit.to_string());
+    }
+    promoted.metadata.additional.insert(PROMOTED_FROM_KEY.to_string(), provenance);
+
+    promoted
+}
+
+/// Path for a promoted object that has no counterpart on the target
+pub fn new_object_path(target_instance: &str, module_id: &str, content_type: &str, source_path: &str) -> Result<String> {
+    let file_name = Path::new(source_path).file_name()
+        .and_then(|name| name.to_str())
+        .with_context(|| format!("Invalid source path: {source_path}"))?;
+    Ok(format!("{target_instance}/{module_id}/{content_type}/{file_name}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn correlation_searches() -> ContentTypeDefinition {
+        crate::modules::content_type_definition("xsiam", "correlation_searches")
+    }
+
+    fn rule(id: &str, name: &str, rule_id: i64, severity: &str) -> XsiamObject {
+        let mut object = XsiamObject::new(id.to_string(), name.to_string(), "correlation_searches".to_string());
+        object.content.insert("rule_id".to_string(), json!(rule_id));
+        object.content.insert("severity".to_string(), json!(severity));
+        object
+    }
+
+    #[test]
+    fn test_remap_takes_ids_from_counterpart() {
+        let mut source = rule("11", "Login", 11, "high");
+        source.tenant_id = Some("dev-tenant".to_string());
+        let mut target = rule("42", "Login", 42, "low");
+        target.tenant_id = Some("prod-tenant".to_string());
+
+        let promoted = remap_for_target(&correlation_searches(), &source, Some(&target), "dev", Some("abc123"));
+        assert_eq!(promoted.id, "42");
+        assert_eq!(promoted.tenant_id.as_deref(), Some("prod-tenant"));
+        assert_eq!(promoted.content["rule_id"], json!(42));
+        assert_eq!(promoted.content["severity"], json!("high"));
+        assert_eq!(promoted.metadata.additional["promoted_from"], json!({"instance": "dev", "id": "11", "commit": "abc123"}));
+    }
+
+    #[test]
+    fn test_remap_clears_ids_for_new_objects() {
+        let mut source = rule("11", "Login", 11, "high");
+        source.tenant_id = Some("dev-tenant".to_string());
+        source.metadata.version = "7".to_string();
+        source.metadata.created_by = "dev@example.com".to_string();
+        source.metadata.additional.insert("modified_by".to_string(), json!("dev@example.com"));
+        let promoted = remap_for_target(&correlation_searches(), &source, None, "dev", None);
+        assert_eq!(promoted.id, "");
+        assert_eq!(promoted.tenant_id, None);
+        assert!(!promoted.content.contains_key("rule_id"));
+
+        // Source metadata is not carried over, only the provenance
+        let expected = ObjectMetadata {
+            additional: [(PROMOTED_FROM_KEY.to_string(), json!({"instance": "dev", "id": "11"}))].into_iter().collect(),
+            ..ObjectMetadata::default()
+        };
+        assert_eq!(promoted.metadata, expected);
+
+        // The written file reads back even though the ID is still empty
+        let parser = YamlParser::new();
+        let (_, yaml) = parser.object_files(&promoted, "prod/xsiam/correlation_searches/Login.yaml").unwrap().remove(0);
+        let parsed = parser.parse_content(&String::from_utf8(yaml).unwrap(), "Login.yaml").unwrap();
+        assert_eq!(parsed.id, "");
+    }
+
+    #[test]
+    fn test_select_by_id_or_name() {
+        let objects = vec![
+            LocalObject { path: "a.yaml".to_string(), object: rule("1", "Login", 1, "low") },
+            LocalObject { path: "b.yaml".to_string(), object: rule("2", "Logout", 2, "low") },
+        ];
+        let selected = select_objects(&objects, &["2".to_string(), "Login".to_string(), "Logout".to_string()]).unwrap();
+        assert_eq!(selected.len(), 2);
+        assert!(select_objects(&objects, &["Missing".to_string()]).is_err());
+    }
+}
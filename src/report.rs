@@ -0,0 +1,235 @@
+// SPDX-FileCopyrightText: GoCortexIO
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+// Change reports between two revisions of an instance repository
+// Grouped by module, then content type, then object, with field-level changes
+
+use anyhow::{Context, Result};
+use git2::Delta;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+use crate::git_wrapper::GitWrapper;
+use crate::object_diff::{self, FieldChange};
+use crate::parser::YamlParser;
+use crate::sidecar;
+
+/// Longest rendered field value before truncation
+const MAX_VALUE_LEN: usize = 80;
+
+/// A changed object between two revisions
+#[derive(Debug)]
+pub struct ObjectChange {
+    pub module: String,
+    pub content_type: String,
+    /// Object name, or the file name when the object has none
+    pub name: String,
+    pub change: Delta,
+    pub fields: Vec<FieldChange>,
+}
+
+/// Collect object changes between two revisions
+/// Only files laid out as module/content_type/object.yaml are included.
+pub fn build_report(git_wrapper: &GitWrapper, yaml_parser: &YamlParser, from: &str, to: &str) -> Result<Vec<ObjectChange>> {
+    let mut changes = Vec::new();
+
+    // Sidecar changes are folded into their object so code edits are reported against it
+    for (path, delta) in sidecar::object_changes(&git_wrapper.diff_revisions(from, to)?) {
+        let segments: Vec<&str> = path.split('/').collect();
+        if segments.len() != 3 || segments[1].starts_with('.') || !(path.ends_with(".yaml") || path.ends_with(".yml")) {
+            continue;
+        }
+
+        let old = read_object(git_wrapper, yaml_parser, from, &path)?;
+        let new = read_object(git_wrapper, yaml_parser, to, &path)?;
+
+        let file_stem = segments[2].trim_end_matches(".yaml").trim_end_matches(".yml");
+        let name = new.as_ref().or(old.as_ref())
+            .and_then(|object| object.name.clone())
+            .unwrap_or_else(|| file_stem.to_string());
+
+        let fields = match (&old, &new) {
+            (Some(old), Some(new)) => object_diff::field_changes(old, new),
+            _ => Vec::new(),
+        };
+
+        changes.push(ObjectChange {
+            module: segments[0].to_string(),
+            content_type: segments[1].to_string(),
+            name,
+            change: delta,
+            fields,
+        });
+    }
+
+    Ok(changes)
+}
+
+fn read_object(git_wrapper: &GitWrapper, yaml_parser: &YamlParser, rev: &str, path: &str) -> Result<Option<crate::types::XsiamObject>> {
+    let Some(content) = git_wrapper.read_file_at_revision(rev, path)? else {
+        return Ok(None);
+    };
+
+    let object = yaml_parser.parse_content(&content, path)
+        .with_context(|| format!("Failed to read {path} at {rev}"))?;
+    let dir = path.rsplit_once('/').map(|(dir, _)| dir).unwrap_or_default();
+    let object = sidecar::join(object, |file_name| git_wrapper.read_file_at_revision(rev, &format!("{dir}/{file_name}")))
+        .with_context(|| format!("Failed to read sidecar files of {path} at {rev}"))?;
+    Ok(Some(object))
+}
+
+/// Group changes by module, then content type
+fn group(changes: &[ObjectChange]) -> BTreeMap<&str, BTreeMap<&str, Vec<&ObjectChange>>> {
+    let mut grouped: BTreeMap<&str, BTreeMap<&str, Vec<&ObjectChange>>> = BTreeMap::new();
+    for change in changes {
+        grouped.entry(change.module.as_str())
+            .or_default()
+            .entry(change.content_type.as_str())
+            .or_default()
+            .push(change);
+    }
+    for content_types in grouped.values_mut() {
+        for objects in content_types.values_mut() {
+            objects.sort_by(|a, b| a.name.cmp(&b.name));
+        }
+    }
+    grouped
+}
+
+fn change_label(delta: Delta) -> &'static str {
+    match delta {
+        Delta::Added => "Added",
+        Delta::Deleted => "Removed",
+        _ => "Modified",
+    }
+}
+
+fn render_value(value: &Option<Value>) -> String {
+    let rendered = match value {
+        None => return "(absent)".to_string(),
+        Some(Value::String(s)) => s.clone(),
+        Some(other) => other.to_string(),
+    };
+    let single_line = rendered.replace('\n', " ");
+    if single_line.chars().count() > MAX_VALUE_LEN {
+        let truncated: String = single_line.chars().take(MAX_VALUE_LEN - 3).collect();
+        format!("{truncated}...")
+    } else {
+        single_line
+    }
+}
+
+/// Render a Markdown report
+pub fn render_markdown(title: &str, changes: &[ObjectChange]) -> String {
+    let mut out = format!("# {title}\n\n");
+    if changes.is_empty() {
+        out.push_str("No object changes.\n");
+        return out;
+    }
+
+    out.push_str(&format!("{} object(s) changed.\n", changes.len()));
+    for (module, content_types) in group(changes) {
+        out.push_str(&format!("\n## {module}\n"));
+        for (content_type, objects) in content_types {
+            out.push_str(&format!("\n### {content_type}\n\n"));
+            for object in objects {
+                out.push_str(&format!("- **{}** ({})\n", object.name, change_label(object.change)));
+                for field in &object.fields {
+                    out.push_str(&format!(
+                        "  - `{}`: `{}` → `{}`\n",
+                        field.path,
+                        render_value(&field.old).replace('`', "'"),
+                        render_value(&field.new).replace('`', "'")
+                    ));
+                }
+            }
+        }
+    }
+
+    out
+}
+
+/// Render a standalone HTML report
+pub fn render_html(title: &str, changes: &[ObjectChange]) -> String {
+    let mut out = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{0}</title>\n</head>\n<body>\n<h1>{0}</h1>\n",
+        escape_html(title)
+    );
+
+    if changes.is_empty() {
+        out.push_str("<p>No object changes.</p>\n");
+    } else {
+        out.push_str(&format!("<p>{} object(s) changed.</p>\n", changes.len()));
+        for (module, content_types) in group(changes) {
+            out.push_str(&format!("<h2>{}</h2>\n", escape_html(module)));
+            for (content_type, objects) in content_types {
+                out.push_str(&format!("<h3>{}</h3>\n<ul>\n", escape_html(content_type)));
+                for object in objects {
+                    out.push_str(&format!("<li><strong>{}</strong> ({})", escape_html(&object.name), change_label(object.change)));
+                    if !object.fields.is_empty() {
+                        out.push_str("\n<table>\n<tr><th>Field</th><th>Before</th><th>After</th></tr>\n");
+                        for field in &object.fields {
+                            out.push_str(&format!(
+                                "<tr><td><code>{}</code></td><td><code>{}</code></td><td><code>{}</code></td></tr>\n",
+                                escape_html(&field.path),
+                                escape_html(&render_value(&field.old)),
+                                escape_html(&render_value(&field.new))
+                            ));
+                        }
+                        out.push_str("</table>\n");
+                    }
+                    out.push_str("</li>\n");
+                }
+                out.push_str("</ul>\n");
+            }
+        }
+    }
+
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn sample_changes() -> Vec<ObjectChange> {
+        vec![
+            ObjectChange {
+                module: "xsiam".to_string(),
+                content_type: "biocs".to_string(),
+                name: "Rule <A>".to_string(),
+                change: Delta::Modified,
+                fields: vec![FieldChange { path: "severity".to_string(), old: Some(json!("low")), new: Some(json!("high")) }],
+            },
+            ObjectChange {
+                module: "xsiam".to_string(),
+                content_type: "biocs".to_string(),
+                name: "New Rule".to_string(),
+                change: Delta::Added,
+                fields: Vec::new(),
+            },
+        ]
+    }
+
+    #[test]
+    fn test_render_markdown_groups_changes() {
+        let report = render_markdown("Changes", &sample_changes());
+        assert!(report.contains("## xsiam\n\n### biocs\n\n- **New Rule** (Added)\n- **Rule <A>** (Modified)\n  - `severity`: `low` → `high`\n"));
+    }
+
+    #[test]
+    fn test_render_html_escapes_names() {
+        let report = render_html("Changes", &sample_changes());
+        assert!(report.contains("<strong>Rule &lt;A&gt;</strong> (Modified)"));
+        assert!(report.contains("<tr><td><code>severity</code></td><td><code>low</code></td><td><code>high</code></td></tr>"));
+    }
+}
@@ -0,0 +1,113 @@
+// SPDX-FileCopyrightText: GoCortexIO
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+// Byte-exact handling of script code
+// The code is kept exactly as the platform returns it, with a SHA-256 of those bytes
+// stored in the object metadata so local files can be proven to match.
+
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::types::XsiamObject;
+
+/// Metadata key holding the SHA-256 of the code as returned by the platform
+pub const CODE_HASH_KEY: &str = "code_sha256";
+
+/// Code from the reply of the script code endpoint, without altering it
+/// The reply is a JSON string holding the code; parsing the response already decoded it once,
+/// so the string is taken as is, quotes and escape sequences included. Other types yield None.
+pub fn decode_reply(reply: &Value) -> Option<String> {
+    reply.as_str().map(str::to_string)
+}
+
+/// Lower-case hex SHA-256 of some bytes
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+/// Result of checking an object's code against its stored hash
+#[derive(Debug, PartialEq)]
+pub enum CodeCheck {
+    /// The code hashes to the stored value
+    Match,
+    /// The code differs from what was pulled
+    Mismatch { expected: String, actual: String },
+    /// The object was pulled before hashes were recorded
+    NoHash,
+    /// The object has no code
+    NoCode,
+}
+
+/// Check that an object's code reproduces the platform bytes recorded at pull time
+pub fn check(object: &XsiamObject) -> CodeCheck {
+    let Some(Value::String(code)) = object.content.get("code") else {
+        return CodeCheck::NoCode;
+    };
+    let Some(Value::String(expected)) = object.metadata.additional.get(CODE_HASH_KEY) else {
+        return CodeCheck::NoHash;
+    };
+
+    let actual = sha256_hex(code.as_bytes());
+    if actual == *expected {
+        CodeCheck::Match
+    } else {
+        CodeCheck::Mismatch { expected: expected.clone(), actual }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_decode_reply_keeps_escape_sequences() {
+        // A literal backslash-n inside a string literal must survive
+        let code = "print('a\\nb')\nreturn 1\n";
+        assert_eq!(decode_reply(&json!(code)).as_deref(), Some(code));
+
+        // Code that is itself quoted comes back unchanged
+        for quoted in ["\"abc\"", "\"use strict\"", "\"C:\\\\temp\""] {
+            let response: Value = serde_json::from_str(&json!({"reply": quoted}).to_string()).unwrap();
+            assert_eq!(decode_reply(&response["reply"]).as_deref(), Some(quoted));
+        }
+
+        assert_eq!(decode_reply(&json!({"code": code})), None);
+    }
+
+    #[test]
+    fn test_check_against_stored_hash() {
+        let code = "print('a\\nb')\n";
+        let mut object = XsiamObject::new("uid".to_string(), "Script".to_string(), "scripts".to_string());
+        assert_eq!(check(&object), CodeCheck::NoCode);
+
+        object.content.insert("code".to_string(), json!(code));
+        assert_eq!(check(&object), CodeCheck::NoHash);
+
+        object.metadata.additional.insert(CODE_HASH_KEY.to_string(), json!(sha256_hex(code.as_bytes())));
+        assert_eq!(check(&object), CodeCheck::Match);
+
+        object.content.insert("code".to_string(), json!("print('a\nb')\n"));
+        assert!(matches!(check(&object), CodeCheck::Mismatch { .. }));
+    }
+
+    #[test]
+    fn test_check_survives_normalisation_and_reverse_templating() {
+        let code = "dataset = \"prod_logs_raw\"\n";
+        let mut object = XsiamObject::new("uid".to_string(), "Script".to_string(), "scripts".to_string());
+        object.content.insert("code".to_string(), json!(code));
+        object.metadata.additional.insert(CODE_HASH_KEY.to_string(), json!(sha256_hex(code.as_bytes())));
+
+        let rules = toml::from_str("[scripts]\nzero = [\"code\"]\n").unwrap();
+        let vars = std::collections::BTreeMap::from([("dataset".to_string(), "prod_logs_raw".to_string())]);
+        let stored = crate::templating::Templating::new("test_script_code_check", vars)
+            .reverse_map(&crate::normalise::Normaliser::new(rules).apply(&object), "xsiam/scripts/Script.yaml", None)
+            .unwrap();
+        assert_eq!(check(&stored), CodeCheck::Match);
+    }
+
+    #[test]
+    fn test_sha256_hex() {
+        assert_eq!(sha256_hex(b"abc"), "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+    }
+}
@@ -0,0 +1,244 @@
+// SPDX-FileCopyrightText: GoCortexIO
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+// Sidecar source files for large text fields
+// Fields such as script code are written to a file next to the object YAML, which records
+// the file name in <field>_file. The field is read back from the sidecar when the object is loaded.
+// XQL queries are only written to sidecars when the instance enables [layout] xql_files,
+// but any sidecar an object refers to is always read back.
+
+use anyhow::{Context, Result};
+use git2::Delta;
+use serde_json::Value;
+use std::path::Path;
+
+use crate::config::LayoutSettings;
+use crate::git_wrapper::FileChange;
+use crate::metadata_index;
+use crate::types::XsiamObject;
+
+/// Suffix of the YAML key that names a field's sidecar file
+pub const FILE_SUFFIX: &str = "_file";
+
+/// Content fields that may be stored in sidecar files for a content type
+pub fn sidecar_fields(content_type: &str) -> &'static [&'static str] {
+    match content_type {
+        "scripts" => &["code"],
+        "correlation_searches" | "scheduled_queries" | "xql_library" => &["xql_query"],
+        _ => &[],
+    }
+}
+
+/// Whether a sidecar field is written to its own file under the given layout
+fn writes_sidecar(field: &str, layout: &LayoutSettings) -> bool {
+    match field {
+        "xql_query" => layout.xql_files,
+        _ => true,
+    }
+}
+
+/// File extension for a sidecar, from the object's language where the field is code
+fn sidecar_extension(object: &XsiamObject, field: &str) -> &'static str {
+    if field == "xql_query" {
+        return "xql";
+    }
+    if field != "code" {
+        return "txt";
+    }
+    let language = ["language", "script_type", "type"].iter()
+        .find_map(|key| object.content.get(*key).and_then(Value::as_str))
+        .unwrap_or_default()
+        .to_lowercase();
+    match language.as_str() {
+        "python" | "python2" | "python3" => "py",
+        "javascript" | "js" => "js",
+        "powershell" => "ps1",
+        _ => "txt",
+    }
+}
+
+/// Move sidecar fields out of an object
+/// Returns the object to serialise as YAML and the (file name, content) of each sidecar.
+/// Sidecars are named after the YAML file, e.g. Cleanup.yaml -> Cleanup.py.
+pub fn split(object: &XsiamObject, yaml_path: &str, layout: &LayoutSettings) -> (XsiamObject, Vec<(String, String)>) {
+    let stem = Path::new(yaml_path).file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or(&object.id)
+        .to_string();
+
+    let mut stripped = object.clone();
+    let mut sidecars = Vec::new();
+    let fields = sidecar_fields(&object.content_type);
+    for field in fields.iter().filter(|field| writes_sidecar(field, layout)) {
+        let Some(Value::String(text)) = object.content.get(*field) else {
+            continue;
+        };
+        // Objects with several sidecar fields keep the field name in the file name
+        let file_name = if fields.len() == 1 {
+            format!("{stem}.{}", sidecar_extension(object, field))
+        } else {
+            format!("{stem}.{field}.{}", sidecar_extension(object, field))
+        };
+        stripped.content.remove(*field);
+        stripped.content.insert(format!("{field}{FILE_SUFFIX}"), Value::String(file_name.clone()));
+        sidecars.push((file_name, text.clone()));
+    }
+
+    (stripped, sidecars)
+}
+
+/// Read sidecar fields back into an object
+/// `read` returns the content of a file next to the YAML, or None if it does not exist.
+pub fn join(object: XsiamObject, read: impl Fn(&str) -> Result<Option<String>>) -> Result<XsiamObject> {
+    let mut joined = object;
+    for field in sidecar_fields(&joined.content_type) {
+        let key = format!("{field}{FILE_SUFFIX}");
+        let Some(Value::String(file_name)) = joined.content.get(&key).cloned() else {
+            continue;
+        };
+        if file_name.contains('/') || file_name.contains('\\') || file_name.starts_with('.') {
+            return Err(anyhow::anyhow!("Sidecar file name '{file_name}' must be a plain file name"));
+        }
+        let text = read(&file_name)?
+            .with_context(|| format!("Sidecar file '{file_name}' referenced by {key} is missing"))?;
+        joined.content.remove(&key);
+        joined.content.insert(field.to_string(), Value::String(text));
+    }
+    Ok(joined)
+}
+
+/// Sidecar file names an object's YAML refers to
+pub fn referenced_files(object: &XsiamObject) -> Vec<String> {
+    sidecar_fields(&object.content_type).iter()
+        .filter_map(|field| object.content.get(&format!("{field}{FILE_SUFFIX}")))
+        .filter_map(|value| value.as_str().map(str::to_string))
+        .collect()
+}
+
+/// Path of the object YAML a sidecar belongs to, if the path looks like a sidecar
+/// Sidecars live next to their YAML and share its file stem.
+pub fn owning_yaml_path(path: &str) -> Option<String> {
+    if path.ends_with(".yaml") || path.ends_with(".yml") {
+        return None;
+    }
+    let (dir, file_name) = path.rsplit_once('/')?;
+    let (mut stem, _) = file_name.rsplit_once('.')?;
+    let content_type = dir.rsplit('/').next().unwrap_or(dir);
+    let fields = sidecar_fields(content_type);
+    if fields.len() > 1 {
+        if let Some(field_stem) = fields.iter().find_map(|field| stem.strip_suffix(&format!(".{field}"))) {
+            stem = field_stem;
+        }
+    }
+    (!stem.is_empty()).then(|| format!("{dir}/{stem}.yaml"))
+}
+
+/// Collapse file changes to one change per object YAML
+/// A change to a sidecar counts as a modification of its object unless the YAML itself changed.
+/// Metadata index changes are left out.
+pub fn object_changes(changes: &[FileChange]) -> Vec<FileChange> {
+    let mut objects: Vec<FileChange> = Vec::new();
+    // Metadata index files are not objects
+    for (path, delta) in changes.iter().filter(|(path, _)| !metadata_index::is_index_path(path)) {
+        let (yaml_path, delta) = match owning_yaml_path(path) {
+            Some(yaml_path) => (yaml_path, Delta::Modified),
+            None => (path.clone(), *delta),
+        };
+        match objects.iter_mut().find(|(existing, _)| *existing == yaml_path) {
+            Some(existing) if path == &yaml_path => existing.1 = delta,
+            Some(_) => {}
+            None => objects.push((yaml_path, delta)),
+        }
+    }
+    objects
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    fn script(language: &str) -> XsiamObject {
+        let mut object = XsiamObject::new("uid-1".to_string(), "Cleanup".to_string(), "scripts".to_string());
+        object.content.insert("code".to_string(), json!("def main():\n    return 'a\\nb'\n"));
+        object.content.insert("script_type".to_string(), json!(language));
+        object
+    }
+
+    #[test]
+    fn test_split_and_join_round_trip() {
+        let original = script("python3");
+        let (stripped, sidecars) = split(&original, "xsiam/scripts/Cleanup.yaml", &LayoutSettings::default());
+
+        assert!(!stripped.content.contains_key("code"));
+        assert_eq!(stripped.content["code_file"], json!("Cleanup.py"));
+        assert_eq!(sidecars, vec![("Cleanup.py".to_string(), "def main():\n    return 'a\\nb'\n".to_string())]);
+
+        let files: HashMap<String, String> = sidecars.into_iter().collect();
+        let joined = join(stripped, |name| Ok(files.get(name).cloned())).unwrap();
+        assert_eq!(joined, original);
+    }
+
+    #[test]
+    fn test_extension_follows_language() {
+        assert_eq!(split(&script("javascript"), "Cleanup.yaml", &LayoutSettings::default()).1[0].0, "Cleanup.js");
+        assert_eq!(split(&script("powershell"), "Cleanup.yaml", &LayoutSettings::default()).1[0].0, "Cleanup.ps1");
+        assert_eq!(split(&script("unknown"), "Cleanup.yaml", &LayoutSettings::default()).1[0].0, "Cleanup.txt");
+    }
+
+    #[test]
+    fn test_join_rejects_missing_or_unsafe_sidecars() {
+        let (stripped, _) = split(&script("python"), "Cleanup.yaml", &LayoutSettings::default());
+        assert!(join(stripped.clone(), |_| Ok(None)).is_err());
+
+        let mut unsafe_ref = stripped;
+        unsafe_ref.content.insert("code_file".to_string(), json!("../../secrets.py"));
+        assert!(join(unsafe_ref, |_| Ok(Some(String::new()))).is_err());
+    }
+
+    #[test]
+    fn test_xql_sidecars_follow_layout() {
+        let mut rule = XsiamObject::new("7".to_string(), "Failed Logins".to_string(), "correlation_searches".to_string());
+        rule.content.insert("xql_query".to_string(), json!("dataset = xdr_data\n| filter action = \"login\"\n"));
+
+        let (embedded, sidecars) = split(&rule, "xsiam/correlation_searches/Failed_Logins.yaml", &LayoutSettings::default());
+        assert!(sidecars.is_empty());
+        assert_eq!(embedded, rule);
+
+        let layout = LayoutSettings { xql_files: true, ..LayoutSettings::default() };
+        let (stripped, sidecars) = split(&rule, "xsiam/correlation_searches/Failed_Logins.yaml", &layout);
+        assert_eq!(stripped.content["xql_query_file"], json!("Failed_Logins.xql"));
+        assert_eq!(sidecars[0].0, "Failed_Logins.xql");
+
+        // Sidecars are read back whatever the layout
+        let files: HashMap<String, String> = sidecars.into_iter().collect();
+        assert_eq!(join(stripped, |name| Ok(files.get(name).cloned())).unwrap(), rule);
+        assert_eq!(
+            owning_yaml_path("xsiam/correlation_searches/Failed_Logins.xql"),
+            Some("xsiam/correlation_searches/Failed_Logins.yaml".to_string())
+        );
+    }
+
+    #[test]
+    fn test_owning_yaml_path() {
+        assert_eq!(owning_yaml_path("xsiam/scripts/Cleanup.py"), Some("xsiam/scripts/Cleanup.yaml".to_string()));
+        assert_eq!(owning_yaml_path("xsiam/scripts/Daily.Cleanup.js"), Some("xsiam/scripts/Daily.Cleanup.yaml".to_string()));
+        assert_eq!(owning_yaml_path("xsiam/scripts/Cleanup.yaml"), None);
+    }
+
+    #[test]
+    fn test_object_changes_fold_sidecars_into_objects() {
+        let changes = vec![
+            ("xsiam/scripts/New.py".to_string(), Delta::Added),
+            ("xsiam/scripts/New.yaml".to_string(), Delta::Added),
+            ("xsiam/scripts/Edited.py".to_string(), Delta::Modified),
+            ("xsiam/biocs/Rule.yaml".to_string(), Delta::Deleted),
+        ];
+        assert_eq!(object_changes(&changes), vec![
+            ("xsiam/scripts/New.yaml".to_string(), Delta::Added),
+            ("xsiam/scripts/Edited.yaml".to_string(), Delta::Modified),
+            ("xsiam/biocs/Rule.yaml".to_string(), Delta::Deleted),
+        ]);
+    }
+}
@@ -0,0 +1,208 @@
+// SPDX-FileCopyrightText: GoCortexIO
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+// Commit signing and signature verification
+// Delegates to ssh-keygen or gpg/gpgv so keys never pass through gcgit itself
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+/// SSH signing namespace used by Git for commit signatures
+const SSH_NAMESPACE: &str = "git";
+
+/// Signature format, matching Git's gpg.format setting
+#[derive(Debug, Default, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SigningFormat {
+    #[default]
+    Ssh,
+    Gpg,
+}
+
+/// Signs commit payloads with an SSH private key or GPG key ID
+#[derive(Debug, Clone)]
+pub struct CommitSigner {
+    pub format: SigningFormat,
+    /// Private key file for SSH, key ID or fingerprint for GPG
+    pub key: String,
+}
+
+impl CommitSigner {
+    /// Produce an armoured signature for a commit payload
+    pub fn sign(&self, payload: &str) -> Result<String> {
+        let output = match self.format {
+            SigningFormat::Ssh => run_with_stdin(
+                Command::new("ssh-keygen").args(["-Y", "sign", "-n", SSH_NAMESPACE, "-f", &self.key]),
+                payload,
+            ),
+            SigningFormat::Gpg => run_with_stdin(
+                Command::new("gpg").args(["--batch", "--armor", "--detach-sign", "--local-user", &self.key]),
+                payload,
+            ),
+        }.context("Failed to sign commit")?;
+
+        String::from_utf8(output).context("Signature is not valid UTF-8")
+    }
+}
+
+/// Verifies commit signatures against a set of trusted keys
+/// For SSH this is an allowed-signers file; for GPG a keyring of exported public keys.
+#[derive(Debug, Clone)]
+pub struct SignatureVerifier {
+    pub format: SigningFormat,
+    pub allowed_signers: PathBuf,
+}
+
+impl SignatureVerifier {
+    /// Verify a signature over a payload, returning the identity of the signer
+    pub fn verify(&self, signature: &str, payload: &str) -> Result<String> {
+        if !self.allowed_signers.exists() {
+            return Err(anyhow::anyhow!("Allowed signers file not found: {}", self.allowed_signers.display()));
+        }
+
+        let signature_file = TempFile::create(signature)?;
+        let allowed = self.allowed_signers.to_string_lossy();
+        let signature_path = signature_file.path.to_string_lossy();
+
+        match self.format {
+            SigningFormat::Ssh => {
+                let principals = run_with_stdin(
+                    Command::new("ssh-keygen").args(["-Y", "find-principals", "-f", &allowed, "-s", &signature_path]),
+                    "",
+                ).context("Signing key is not in the allowed signers file")?;
+                let principals = String::from_utf8_lossy(&principals);
+                let principal = principals.lines().next()
+                    .ok_or_else(|| anyhow::anyhow!("Signing key is not in the allowed signers file"))?
+                    .trim()
+                    .to_string();
+
+                run_with_stdin(
+                    Command::new("ssh-keygen").args(["-Y", "verify", "-n", SSH_NAMESPACE, "-f", &allowed, "-I", &principal, "-s", &signature_path]),
+                    payload,
+                ).context("SSH signature does not verify")?;
+                Ok(principal)
+            }
+            SigningFormat::Gpg => {
+                let output = Command::new("gpgv")
+                    .args(["--status-fd", "1", "--keyring", &allowed, &signature_path, "-"])
+                    .stdin(Stdio::piped())
+                    .stdout(Stdio::piped())
+                    .stderr(Stdio::piped())
+                    .spawn()
+                    .and_then(|mut child| {
+                        if let Some(mut stdin) = child.stdin.take() {
+                            stdin.write_all(payload.as_bytes())?;
+                        }
+                        child.wait_with_output()
+                    })
+                    .context("Failed to run gpgv")?;
+                if !output.status.success() {
+                    return Err(anyhow::anyhow!("GPG signature does not verify: {}", String::from_utf8_lossy(&output.stderr).trim()));
+                }
+                // [GNUPG:] GOODSIG <keyid> <user id>
+                let status = String::from_utf8_lossy(&output.stdout);
+                let signer = status.lines()
+                    .find_map(|line| line.strip_prefix("[GNUPG:] GOODSIG "))
+                    .and_then(|rest| rest.split_once(' '))
+                    .map(|(_, user)| user.to_string())
+                    .unwrap_or_else(|| "unknown".to_string());
+                Ok(signer)
+            }
+        }
+    }
+}
+
+/// Run a command with the given stdin, returning stdout or an error containing stderr
+fn run_with_stdin(command: &mut Command, input: &str) -> Result<Vec<u8>> {
+    let program = command.get_program().to_string_lossy().into_owned();
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("Failed to run {program}"))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(input.as_bytes())
+            .with_context(|| format!("Failed to write to {program}"))?;
+    }
+
+    let output = child.wait_with_output()
+        .with_context(|| format!("Failed to wait for {program}"))?;
+    if !output.status.success() {
+        return Err(anyhow::anyhow!("{program} failed: {}", String::from_utf8_lossy(&output.stderr).trim()));
+    }
+
+    Ok(output.stdout)
+}
+
+/// Temporary file removed on drop; signature tools only read signatures from files
+struct TempFile {
+    path: PathBuf,
+}
+
+impl TempFile {
+    fn create(content: &str) -> Result<Self> {
+        let path = std::env::temp_dir().join(format!("gcgit-{}.sig", uuid::Uuid::new_v4()));
+        std::fs::write(&path, content)
+            .with_context(|| format!("Failed to write temporary file: {}", path.display()))?;
+        Ok(Self { path })
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git_wrapper::GitWrapper;
+    use std::fs;
+
+    #[test]
+    fn test_ssh_signed_commit_verifies() {
+        let test_dir = "test_signing_instance";
+        let _ = fs::remove_dir_all(test_dir);
+        fs::create_dir(test_dir).unwrap();
+
+        let key_path = format!("{test_dir}/signing_key");
+        let generated = Command::new("ssh-keygen")
+            .args(["-q", "-t", "ed25519", "-N", "", "-C", "gcgit-test", "-f", &key_path])
+            .status();
+        if !generated.is_ok_and(|status| status.success()) {
+            // ssh-keygen is unavailable; nothing to test
+            let _ = fs::remove_dir_all(test_dir);
+            return;
+        }
+
+        let public_key = fs::read_to_string(format!("{key_path}.pub")).unwrap();
+        let allowed_signers = format!("{test_dir}/allowed_signers");
+        fs::write(&allowed_signers, format!("compliance@example.com {public_key}")).unwrap();
+
+        let mut git = GitWrapper::new(test_dir).unwrap();
+        fs::write(format!("{test_dir}/rule.yaml"), "v: 1\n").unwrap();
+        git.add_files(&["rule.yaml".to_string()]).unwrap();
+        git.commit("Unsigned").unwrap();
+
+        git.set_signer(Some(CommitSigner { format: SigningFormat::Ssh, key: key_path.clone() }));
+        fs::write(format!("{test_dir}/rule.yaml"), "v: 2\n").unwrap();
+        git.add_files(&["rule.yaml".to_string()]).unwrap();
+        git.commit("Signed").unwrap();
+
+        let verifier = SignatureVerifier { format: SigningFormat::Ssh, allowed_signers: PathBuf::from(&allowed_signers) };
+        assert_eq!(git.verify_commit("HEAD", &verifier).unwrap(), "compliance@example.com");
+        assert!(git.verify_commit("HEAD~1", &verifier).is_err());
+
+        // A key missing from the allowed signers file is rejected
+        fs::write(&allowed_signers, "").unwrap();
+        assert!(git.verify_commit("HEAD", &verifier).is_err());
+
+        let _ = fs::remove_dir_all(test_dir);
+    }
+}
@@ -0,0 +1,84 @@
+// SPDX-FileCopyrightText: GoCortexIO
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+// Sync state reported by the status command
+// Remote drift is estimated from object modification timestamps, avoiding a full diff
+
+use chrono::{DateTime, Utc};
+
+use crate::types::XsiamObject;
+
+/// How a content type on the platform has changed since the last pull
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct RemoteDrift {
+    /// Objects whose modification time is after the last pull
+    pub modified: usize,
+    /// Objects without a modification time, which cannot be checked
+    pub undated: usize,
+    pub local_count: usize,
+    pub remote_count: usize,
+}
+
+impl RemoteDrift {
+    /// Compare platform objects with the last pull time and the local object count
+    pub fn assess(remote: &[XsiamObject], local_count: usize, last_pull: DateTime<Utc>) -> Self {
+        let mut drift = Self { local_count, remote_count: remote.len(), ..Self::default() };
+        for object in remote {
+            match object.metadata.updated_at {
+                Some(updated_at) if updated_at > last_pull => drift.modified += 1,
+                Some(_) => {}
+                None => drift.undated += 1,
+            }
+        }
+        drift
+    }
+
+    pub fn has_drift(&self) -> bool {
+        self.modified > 0 || self.local_count != self.remote_count
+    }
+
+    /// Short description for status output, e.g. "2 modified, 12 -> 13 objects"
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        if self.modified > 0 {
+            parts.push(format!("{} modified", self.modified));
+        }
+        if self.local_count != self.remote_count {
+            parts.push(format!("{} -> {} objects", self.local_count, self.remote_count));
+        }
+        if parts.is_empty() {
+            parts.push("in sync".to_string());
+        }
+        if self.undated > 0 {
+            parts.push(format!("{} without timestamps", self.undated));
+        }
+        parts.join(", ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn object(id: &str, updated_at: Option<DateTime<Utc>>) -> XsiamObject {
+        let mut object = XsiamObject::new(id.to_string(), id.to_string(), "biocs".to_string());
+        object.metadata.updated_at = updated_at;
+        object
+    }
+
+    #[test]
+    fn test_assess_drift() {
+        let last_pull = Utc.with_ymd_and_hms(2026, 10, 1, 12, 0, 0).unwrap();
+        let before = Some(Utc.with_ymd_and_hms(2026, 9, 30, 8, 0, 0).unwrap());
+        let after = Some(Utc.with_ymd_and_hms(2026, 10, 2, 8, 0, 0).unwrap());
+
+        let unchanged = RemoteDrift::assess(&[object("1", before), object("2", before)], 2, last_pull);
+        assert!(!unchanged.has_drift());
+        assert_eq!(unchanged.describe(), "in sync");
+
+        let drifted = RemoteDrift::assess(&[object("1", after), object("2", before), object("3", None)], 2, last_pull);
+        assert!(drifted.has_drift());
+        assert_eq!(drifted.describe(), "1 modified, 2 -> 3 objects, 1 without timestamps");
+    }
+}
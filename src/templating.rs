@@ -0,0 +1,297 @@
+// SPDX-FileCopyrightText: GoCortexIO
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+// Per-instance variables and overlays for object YAML
+// Base objects may contain {{ var }} placeholders and be patched by overlay files;
+// both are resolved into the payload sent to the platform at push and diff time.
+
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::sidecar;
+use crate::types::XsiamObject;
+
+/// Directory inside an instance holding overlay files, mirroring module/content_type/file.yaml
+pub const OVERLAY_DIR: &str = "overlays";
+
+/// Resolves placeholders and overlays for one instance
+pub struct Templating {
+    instance_name: String,
+    vars: BTreeMap<String, String>,
+}
+
+impl Templating {
+    pub fn new(instance_name: &str, vars: BTreeMap<String, String>) -> Self {
+        Self { instance_name: instance_name.to_string(), vars }
+    }
+
+    /// Render an object into the form sent to the platform
+    /// The overlay for the object's path (relative to the instance) is merged in first,
+    /// then placeholders of declared variables are replaced. Other {{ }} text is left as is.
+    pub fn render(&self, object: &XsiamObject, relative_path: &str) -> Result<XsiamObject> {
+        let mut fields = object.fields();
+        if let Some(overlay) = self.overlay(relative_path)? {
+            merge_overlay(&mut fields, overlay);
+        }
+        map_strings(&mut fields, &mut |text| self.substitute_text(text));
+
+        Ok(object.with_fields(fields))
+    }
+
+    /// Whether an overlay file exists for the object path (relative to the instance)
+    pub fn has_overlay(&self, relative_path: &str) -> bool {
+        self.overlay_path(relative_path).exists()
+    }
+
+    /// Turn a pulled object back into its base form, the reverse of render
+    /// Fields the overlay sets keep the base object's value while the platform still has the
+    /// overlaid value. Known variable values are then replaced with their placeholders where
+    /// they stand as whole tokens; code and XQL fields are never rewritten.
+    pub fn reverse_map(&self, object: &XsiamObject, relative_path: &str, base: Option<&XsiamObject>) -> Result<XsiamObject> {
+        let mut fields = object.fields();
+        if let Some(mut overlay) = self.overlay(relative_path)? {
+            map_strings(&mut overlay, &mut |text| self.substitute_text(text));
+            let base_fields = base.map(XsiamObject::fields);
+            restore_overlaid(&mut fields, &overlay, base_fields.as_ref());
+        }
+
+        let mut replacements: Vec<(&String, &String)> = self.vars.iter()
+            .filter(|(_, value)| !value.is_empty())
+            .collect();
+        replacements.sort_by_key(|(_, value)| std::cmp::Reverse(value.len()));
+
+        if let Value::Object(map) = &mut fields {
+            let verbatim = sidecar::sidecar_fields(&object.content_type);
+            for (key, value) in map.iter_mut() {
+                if !verbatim.contains(&key.as_str()) {
+                    map_strings(value, &mut |text| replace_tokens(text, &replacements));
+                }
+            }
+        }
+
+        Ok(object.with_fields(fields))
+    }
+
+    fn overlay_path(&self, relative_path: &str) -> PathBuf {
+        Path::new(&self.instance_name).join(OVERLAY_DIR).join(relative_path)
+    }
+
+    /// Read the overlay for an object path, if there is one
+    fn overlay(&self, relative_path: &str) -> Result<Option<Value>> {
+        let overlay_path = self.overlay_path(relative_path);
+        if !overlay_path.exists() {
+            return Ok(None);
+        }
+        let content = fs::read_to_string(&overlay_path)
+            .with_context(|| format!("Failed to read overlay: {}", overlay_path.display()))?;
+        let value: Value = serde_yaml_ng::from_str(&content)
+            .with_context(|| format!("Failed to parse overlay: {}", overlay_path.display()))?;
+        Ok(Some(value))
+    }
+
+    /// Replace {{ name }} placeholders of declared variables
+    fn substitute_text(&self, text: &str) -> String {
+        let mut result = String::new();
+        let mut rest = text;
+
+        while let Some(start) = rest.find("{{") {
+            let Some(end) = rest[start..].find("}}") else {
+                break;
+            };
+            let name = rest[start + 2..start + end].trim();
+            result.push_str(&rest[..start]);
+            match self.vars.get(name) {
+                Some(value) => result.push_str(value),
+                None => result.push_str(&rest[start..start + end + 2]),
+            }
+            rest = &rest[start + end + 2..];
+        }
+        result.push_str(rest);
+
+        result
+    }
+}
+
+/// Put base values back for fields where the pulled value is still the overlay's
+/// Fields the base did not have are removed; fields changed on the platform are kept.
+fn restore_overlaid(fields: &mut Value, overlay: &Value, base: Option<&Value>) {
+    let (Value::Object(map), Value::Object(overlay_map)) = (fields, overlay) else {
+        return;
+    };
+
+    for (key, overlay_value) in overlay_map {
+        let base_value = base.and_then(|base| base.get(key));
+        let Some(value) = map.get_mut(key) else {
+            continue;
+        };
+        if value.is_object() && overlay_value.is_object() {
+            restore_overlaid(value, overlay_value, base_value);
+            continue;
+        }
+        if value != overlay_value {
+            continue;
+        }
+        match base_value {
+            Some(base_value) => *value = base_value.clone(),
+            None => {
+                map.remove(key);
+            }
+        }
+    }
+}
+
+/// Replace variable values that stand as whole tokens with their placeholders
+/// A value is a whole token when no letter, digit or underscore touches either end,
+/// so a value of prod leaves production alone. Replacements are tried longest first.
+fn replace_tokens(text: &str, replacements: &[(&String, &String)]) -> String {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    let mut result = String::new();
+    let mut position = 0;
+
+    'scan: while position < text.len() {
+        let preceded_by_word = text[..position].chars().next_back().is_some_and(is_word);
+        if !preceded_by_word {
+            for (name, value) in replacements {
+                let rest = &text[position..];
+                if rest.starts_with(value.as_str()) && !rest[value.len()..].chars().next().is_some_and(is_word) {
+                    result.push_str(&format!("{{{{ {name} }}}}"));
+                    position += value.len();
+                    continue 'scan;
+                }
+            }
+        }
+        let c = text[position..].chars().next().unwrap_or_default();
+        result.push(c);
+        position += c.len_utf8();
+    }
+
+    result
+}
+
+/// Deep-merge an overlay into the fields; maps merge per key, other values replace
+fn merge_overlay(base: &mut Value, overlay: Value) {
+    match (base, overlay) {
+        (Value::Object(base_map), Value::Object(overlay_map)) => {
+            for (key, value) in overlay_map {
+                match base_map.get_mut(&key) {
+                    Some(existing) => merge_overlay(existing, value),
+                    None => {
+                        base_map.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+/// Apply a transformation to every string in a value
+fn map_strings(value: &mut Value, transform: &mut dyn FnMut(&str) -> String) {
+    match value {
+        Value::String(text) => *text = transform(text),
+        Value::Array(items) => {
+            for item in items {
+                map_strings(item, transform);
+            }
+        }
+        Value::Object(map) => {
+            for item in map.values_mut() {
+                map_strings(item, transform);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn vars() -> BTreeMap<String, String> {
+        BTreeMap::from([
+            ("dataset".to_string(), "prod_logs_raw".to_string()),
+            ("soc_email".to_string(), "soc@example.com".to_string()),
+        ])
+    }
+
+    fn rule() -> XsiamObject {
+        let mut object = XsiamObject::new("1".to_string(), "Rule".to_string(), "correlation_searches".to_string());
+        object.content.insert("xql_query".to_string(), json!("dataset = {{ dataset }} | limit 10"));
+        object.content.insert("notify".to_string(), json!(["{{soc_email}}"]));
+        object.content.insert("threshold".to_string(), json!({"count": 5, "window": "1h"}));
+        object
+    }
+
+    #[test]
+    fn test_render_substitutes_variables_and_overlay() {
+        let instance = "test_templating_instance";
+        let _ = fs::remove_dir_all(instance);
+        fs::create_dir_all(format!("{instance}/{OVERLAY_DIR}/xsiam/correlation_searches")).unwrap();
+        fs::write(
+            format!("{instance}/{OVERLAY_DIR}/xsiam/correlation_searches/Rule.yaml"),
+            "threshold:\n  count: 50\n",
+        ).unwrap();
+
+        let templating = Templating::new(instance, vars());
+        let rendered = templating.render(&rule(), "xsiam/correlation_searches/Rule.yaml").unwrap();
+        assert_eq!(rendered.content["xql_query"], json!("dataset = prod_logs_raw | limit 10"));
+        assert_eq!(rendered.content["notify"], json!(["soc@example.com"]));
+        assert_eq!(rendered.content["threshold"], json!({"count": 50, "window": "1h"}));
+
+        let _ = fs::remove_dir_all(instance);
+    }
+
+    #[test]
+    fn test_undeclared_placeholders_are_left_alone() {
+        let templating = Templating::new("test_templating_missing", vars());
+        let mut object = rule();
+        object.content.insert("body".to_string(), json!("Alert {{ alert.name }} for {{ soc_email }}"));
+
+        let rendered = templating.render(&object, "xsiam/correlation_searches/Rule.yaml").unwrap();
+        assert_eq!(rendered.content["body"], json!("Alert {{ alert.name }} for soc@example.com"));
+    }
+
+    #[test]
+    fn test_reverse_map_restores_placeholders() {
+        let mut vars = vars();
+        vars.insert("env".to_string(), "prod".to_string());
+        let templating = Templating::new("test_templating_reverse", vars);
+        let mut pulled = rule();
+        pulled.content.insert("xql_query".to_string(), json!("dataset = prod_logs_raw | limit 10"));
+        pulled.content.insert("notify".to_string(), json!(["soc@example.com"]));
+        pulled.content.insert("label".to_string(), json!("prod production prod-eu"));
+
+        let mapped = templating.reverse_map(&pulled, "xsiam/correlation_searches/Rule.yaml", None).unwrap();
+        assert_eq!(mapped.content["notify"], json!(["{{ soc_email }}"]));
+        assert_eq!(mapped.content["label"], json!("{{ env }} production {{ env }}-eu"));
+        // XQL is stored as pulled
+        assert_eq!(mapped.content["xql_query"], json!("dataset = prod_logs_raw | limit 10"));
+    }
+
+    #[test]
+    fn test_reverse_map_keeps_overlaid_fields_out_of_base() {
+        let instance = "test_templating_reverse_overlay";
+        let _ = fs::remove_dir_all(instance);
+        fs::create_dir_all(format!("{instance}/{OVERLAY_DIR}/xsiam/correlation_searches")).unwrap();
+        fs::write(
+            format!("{instance}/{OVERLAY_DIR}/xsiam/correlation_searches/Rule.yaml"),
+            "threshold:\n  count: 50\nowner: \"{{ soc_email }}\"\n",
+        ).unwrap();
+
+        let templating = Templating::new(instance, vars());
+        let base = rule();
+        let mut pulled = rule();
+        pulled.content.insert("threshold".to_string(), json!({"count": 50, "window": "2h"}));
+        pulled.content.insert("owner".to_string(), json!("soc@example.com"));
+
+        let mapped = templating.reverse_map(&pulled, "xsiam/correlation_searches/Rule.yaml", Some(&base)).unwrap();
+        assert_eq!(mapped.content["threshold"], json!({"count": 5, "window": "2h"}));
+        assert!(!mapped.content.contains_key("owner"));
+
+        let _ = fs::remove_dir_all(instance);
+    }
+}
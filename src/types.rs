@@ -7,6 +7,28 @@ use serde_json::Value;
 use std::collections::HashMap;
 use chrono::{DateTime, Utc};
 
+use crate::modules::{ContentTypeDefinition, IdFallback};
+use sha2::{Digest, Sha256};
+
+/// API fields holding an object's creation time
+const CREATED_TIME_FIELDS: [&str; 6] = [
+    "creation_time", "created_time", "created_at", "createdTime",
+    "date_created", "dateCreated",
+];
+
+/// API fields holding an object's last modification time
+const UPDATED_TIME_FIELDS: [&str; 10] = [
+    "modification_time", "modified_time", "updated_at", "updatedTime",
+    "last_modified", "lastModified", "date_modified", "dateModified",
+    "observationTime", "lastTriggered",
+];
+
+/// Metadata key set on objects whose ID was derived from their content
+const FALLBACK_ID_KEY: &str = "fallback_id";
+
+/// Metadata key recording the instance and object a promoted object was copied from
+pub const PROMOTED_FROM_KEY: &str = "promoted_from";
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct XsiamObject {
     pub id: String,
@@ -14,9 +36,11 @@ pub struct XsiamObject {
     pub name: Option<String>,
     pub description: String,
     pub content_type: String,
+    // Absent from object files when metadata is kept in a metadata index
+    #[serde(default)]
     pub metadata: ObjectMetadata,
     
-    // Authentication settings specific field
+    // Tenant the object belongs to, for content types that declare a tenant_id_field
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tenant_id: Option<String>,
     
@@ -61,116 +85,82 @@ impl XsiamObject {
         }
     }
 
-    pub fn from_api_response(json: &Value, content_type: &str) -> Result<Self> {
-        // Handle different ID field names based on content type
-        let id = match content_type {
-            "correlation_searches" | "biocs" => {
-                // For correlation rules and BIOCs, use rule_id as the primary ID
-                json.get("rule_id")
-                    .and_then(|v| v.as_i64())
-                    .map(|i| i.to_string())
-                    .or_else(|| json.get("id").and_then(|v| v.as_str()).map(|s| s.to_string()))
-                    .unwrap_or_default()
-            }
-            "widgets" => {
-                // Widgets use creation_time or global_id as unique identifier
-                json.get("creation_time")
-                    .and_then(|v| v.as_i64())
-                    .map(|i| i.to_string())
-                    .or_else(|| json.get("global_id").and_then(|v| v.as_str()).map(|s| s.to_string()))
-                    .or_else(|| json.get("widget_id").and_then(|v| v.as_str()).map(|s| s.to_string()))
-                    .or_else(|| json.get("id").and_then(|v| v.as_str()).map(|s| s.to_string()))
-                    .unwrap_or_else(|| format!("widget_{}", chrono::Utc::now().timestamp()))
-            }
-            "dashboards" => {
-                // Dashboards use global_id or default_dashboard_id as unique identifier
-                json.get("global_id")
-                    .and_then(|v| v.as_str())
-                    .map(|s| s.to_string())
-                    .or_else(|| json.get("default_dashboard_id").and_then(|v| v.as_i64()).map(|i| i.to_string()))
-                    .or_else(|| json.get("dashboard_id").and_then(|v| v.as_str()).map(|s| s.to_string()))
-                    .or_else(|| json.get("id").and_then(|v| v.as_str()).map(|s| s.to_string()))
-                    .unwrap_or_else(|| format!("dashboard_{}", chrono::Utc::now().timestamp()))
-            }
-            "authentication_settings" => {
-                // Authentication settings use name field as unique identifier
-                json.get("name")
-                    .and_then(|v| v.as_str())
-                    .map(|s| s.to_string())
-                    .or_else(|| json.get("setting_name").and_then(|v| v.as_str()).map(|s| s.to_string()))
-                    .or_else(|| json.get("type").and_then(|v| v.as_str()).map(|s| s.to_string()))
-                    .unwrap_or_else(|| format!("auth_setting_{}", chrono::Utc::now().timestamp()))
-            }
-            "scheduled_queries" => {
-                json.get("query_def_id")
-                    .and_then(|v| v.as_str().map(|s| s.to_string()).or_else(|| v.as_i64().map(|i| i.to_string())))
-                    .unwrap_or_else(|| format!("scheduled_query_{}", chrono::Utc::now().timestamp()))
-            }
-            "xql_library" => {
-                json.get("id")
-                    .and_then(|v| v.as_i64().map(|i| i.to_string()).or_else(|| v.as_str().map(|s| s.to_string())))
-                    .unwrap_or_else(|| format!("xql_{}", chrono::Utc::now().timestamp()))
-            }
-            "rbac_users" => {
-                json.get("user_email")
-                    .and_then(|v| v.as_str())
-                    .map(|s| s.to_string())
-                    .unwrap_or_else(|| format!("user_{}", chrono::Utc::now().timestamp()))
-            }
-            "application_configuration" => {
-                json.get("id")
-                    .and_then(|v| v.as_str().map(|s| s.to_string()).or_else(|| v.as_i64().map(|i| i.to_string())))
-                    .unwrap_or_else(|| "application_configuration".to_string())
-            }
-            "application_criteria" => {
-                json.get("id")
-                    .and_then(|v| v.as_str().map(|s| s.to_string()).or_else(|| v.as_i64().map(|i| i.to_string())))
-                    .unwrap_or_else(|| format!("criteria_{}", chrono::Utc::now().timestamp()))
-            }
-            _ => {
-                json.get("id")
-                    .and_then(|v| v.as_str())
-                    .map(|s| s.to_string())
-                    .or_else(|| json.get("id").and_then(|v| v.as_i64()).map(|i| i.to_string()))
-                    .unwrap_or_else(|| format!("object_{}", chrono::Utc::now().timestamp()))
-            }
+    /// Name, description, tenant ID and content fields collected into a single JSON map
+    /// ID, content type and metadata are left out; used for diffing, merging and templating.
+    pub fn fields(&self) -> Value {
+        let mut fields = serde_json::Map::new();
+        if let Some(name) = &self.name {
+            fields.insert("name".to_string(), Value::String(name.clone()));
+        }
+        fields.insert("description".to_string(), Value::String(self.description.clone()));
+        if let Some(tenant_id) = &self.tenant_id {
+            fields.insert("tenant_id".to_string(), Value::String(tenant_id.clone()));
+        }
+        for (key, value) in &self.content {
+            fields.insert(key.clone(), value.clone());
+        }
+        Value::Object(fields)
+    }
+
+    /// Clone of the object with its fields replaced by a map built with fields()
+    pub fn with_fields(&self, fields: Value) -> Self {
+        let mut result = self.clone();
+        let Value::Object(mut fields) = fields else {
+            return result;
         };
 
-        let name = match content_type {
-            "widgets" => {
-                // For widgets, use 'title' field as specified: widgets_data.0.title
-                json.get("title")
-                    .and_then(|v| v.as_str())
-                    .or_else(|| json.get("name").and_then(|v| v.as_str()))
-                    .or_else(|| json.get("widget_name").and_then(|v| v.as_str()))
-                    .map(|s| s.to_string())
-            }
-            "dashboards" => {
-                // For dashboards, use 'name' field as specified: dashboards_data.0.name
-                json.get("name")
-                    .and_then(|v| v.as_str())
-                    .map(|s| s.to_string())
-            }
-            "authentication_settings" => {
-                // For authentication settings, use 'name' or 'setting_name' field
-                json.get("name")
-                    .and_then(|v| v.as_str())
-                    .or_else(|| json.get("setting_name").and_then(|v| v.as_str()))
-                    .or_else(|| json.get("type").and_then(|v| v.as_str()))
-                    .map(|s| s.to_string())
-            }
-            "rbac_users" => {
-                json.get("user_email")
-                    .and_then(|v| v.as_str())
-                    .map(|s| s.to_string())
-            }
-            _ => {
-                json.get("name")
+        result.name = fields.remove("name").and_then(|v| v.as_str().map(str::to_string));
+        result.description = fields.remove("description")
+            .and_then(|v| v.as_str().map(str::to_string))
+            .unwrap_or_default();
+        result.tenant_id = fields.remove("tenant_id").and_then(|v| v.as_str().map(str::to_string));
+        result.content = fields.into_iter().collect();
+        result
+    }
+
+    /// Platform user who last changed the object, if the payload records one
+    /// Falls back to the creator when no modifier is recorded.
+    pub fn last_modified_by(&self) -> Option<String> {
+        const USER_FIELDS: [&str; 8] = [
+            "modified_by", "modifiedBy", "last_modified_by", "lastModifiedBy",
+            "updated_by", "updatedBy", "created_by", "createdBy",
+        ];
+
+        USER_FIELDS.iter()
+            .find_map(|field| {
+                self.content.get(*field)
+                    .or_else(|| self.metadata.additional.get(*field))
                     .and_then(|v| v.as_str())
-                    .map(|s| s.to_string())
-            }
+                    .map(|user| user.trim())
+                    .filter(|user| !user.is_empty())
+            })
+            .or_else(|| Some(self.metadata.created_by.trim()).filter(|user| !user.is_empty() && *user != "gcgit"))
+            .map(|user| user.to_string())
+    }
+
+    /// Whether the object's ID was derived from its content because the platform gave none
+    pub fn has_fallback_id(&self) -> bool {
+        self.metadata.additional.get(FALLBACK_ID_KEY).and_then(Value::as_bool).unwrap_or(false)
+    }
+
+    /// Whether the object was promoted from another instance
+    pub fn is_promoted(&self) -> bool {
+        self.metadata.additional.contains_key(PROMOTED_FROM_KEY)
+    }
+
+    /// Build an object from an API response item, using the content type's identity rules
+    pub fn from_api_response(json: &Value, content_def: &ContentTypeDefinition) -> Result<Self> {
+        let content_type = content_def.name;
+
+        // Objects the platform gives no ID get a fixed or content-derived one
+        let (id, fallback_id) = match (content_def.read_id(json), content_def.id_fallback) {
+            (Some(id), _) => (id, false),
+            (None, IdFallback::Fixed(id)) => (id.to_string(), false),
+            (None, IdFallback::ContentHash { prefix, fields }) => (Self::fallback_id(prefix, fields, json), true),
         };
 
+        let name = content_def.read_name(json);
+
         let description = json.get("description")
             .and_then(|v| v.as_str())
             .unwrap_or_default()
@@ -185,16 +175,8 @@ impl XsiamObject {
             }
         } else {
             // Extract timestamps from XSIAM API fields - try multiple common field names
-            metadata.created_at = Self::extract_timestamp_from_json(json, &[
-                "creation_time", "created_time", "created_at", "createdTime", 
-                "date_created", "dateCreated"
-            ]);
-            
-            metadata.updated_at = Self::extract_timestamp_from_json(json, &[
-                "modification_time", "modified_time", "updated_at", "updatedTime",
-                "last_modified", "lastModified", "date_modified", "dateModified",
-                "observationTime", "lastTriggered"
-            ]);
+            metadata.created_at = Self::extract_timestamp_from_json(json, &CREATED_TIME_FIELDS);
+            metadata.updated_at = Self::extract_timestamp_from_json(json, &UPDATED_TIME_FIELDS);
             
             // Extract version from XSIAM API - try multiple version fields
             metadata.version = json.get("version")
@@ -209,31 +191,17 @@ impl XsiamObject {
             metadata.created_by = "gcgit".to_string();
         }
 
-        // Extract tenant_id for authentication_settings
-        let tenant_id = if content_type == "authentication_settings" {
-            json.get("tenant_id")
-                .and_then(|v| {
-                    if let Some(s) = v.as_str() {
-                        Some(s.to_string())
-                    } else { v.as_i64().map(|i| i.to_string()) }
-                })
-        } else {
-            None
-        };
+        let tenant_id = content_def.tenant_id_field.and_then(|field| field.read(json));
+
+        // Objects with a content-derived ID are marked so pulls can report them
+        if fallback_id {
+            metadata.additional.insert(FALLBACK_ID_KEY.to_string(), Value::Bool(true));
+        }
 
-        // Extract additional content, excluding tenant_id if it's for authentication_settings
+        // Extract additional content, leaving out the fields the content type excludes
         let mut content = HashMap::new();
         for (key, value) in json.as_object().unwrap_or(&serde_json::Map::new()) {
-            let should_exclude = matches!(key.as_str(), "id" | "name" | "description" | "metadata") ||
-                (content_type == "authentication_settings" && key == "tenant_id") ||
-                matches!((content_type, key.as_str()),
-                    ("rbac_users", "last_logged_in") |
-                    ("application_criteria", "createdAt") |
-                    ("application_criteria", "lastUpdated") |
-                    ("application_criteria", "deletedAt")
-                );
-            
-            if !should_exclude {
+            if !content_def.is_excluded(key) {
                 content.insert(key.clone(), value.clone());
             }
         }
@@ -249,6 +217,61 @@ impl XsiamObject {
         })
     }
 
+    /// Stable ID for an object the platform gives no ID, e.g. widget_3f2a9c1e0b7d
+    /// Hashes the content type's identity fields, so the ID only changes when one of them does.
+    /// Objects with none of those fields hash their whole content without timestamps instead.
+    fn fallback_id(prefix: &str, identity_fields: &[&str], json: &Value) -> String {
+        let identity: serde_json::Map<String, Value> = identity_fields.iter()
+            .filter_map(|field| json.get(*field).map(|value| (field.to_string(), value.clone())))
+            .collect();
+        let hash = if identity.is_empty() {
+            Self::content_hash(json)
+        } else {
+            Self::content_hash(&Value::Object(identity))
+        };
+        format!("{prefix}_{}", &hash[..12])
+    }
+
+    /// SHA-256 of a JSON value with its timestamp fields left out
+    fn content_hash(json: &Value) -> String {
+        let mut content = json.clone();
+        if let Some(map) = content.as_object_mut() {
+            for field in CREATED_TIME_FIELDS.iter().chain(UPDATED_TIME_FIELDS.iter()) {
+                map.remove(*field);
+            }
+        }
+        // serde_json maps are ordered by key, so the serialisation is canonical
+        format!("{:x}", Sha256::digest(content.to_string().as_bytes()))
+    }
+
+    /// Give pulled objects that share a fallback ID distinct IDs, returning the IDs that collided
+    /// The shared ID gets a hash of each object's fields without timestamps appended, then an
+    /// ordinal where even those are identical, so no object overwrites another's file.
+    pub fn disambiguate_fallback_ids(objects: &mut [XsiamObject]) -> Vec<String> {
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for object in objects.iter().filter(|object| object.has_fallback_id()) {
+            *counts.entry(object.id.clone()).or_insert(0) += 1;
+        }
+        let mut collided: Vec<String> = counts.into_iter()
+            .filter(|(_, count)| *count > 1)
+            .map(|(id, _)| id)
+            .collect();
+        collided.sort();
+
+        let mut seen: HashMap<String, usize> = HashMap::new();
+        for object in objects.iter_mut() {
+            if !object.has_fallback_id() || !collided.contains(&object.id) {
+                continue;
+            }
+            let hash = Self::content_hash(&object.fields());
+            let id = format!("{}_{}", object.id, &hash[..8]);
+            let ordinal = seen.entry(id.clone()).or_insert(0);
+            *ordinal += 1;
+            object.id = if *ordinal > 1 { format!("{id}_{ordinal}") } else { id };
+        }
+        collided
+    }
+
     // Helper method to extract timestamps from JSON with multiple field name attempts
     fn extract_timestamp_from_json(json: &Value, field_names: &[&str]) -> Option<DateTime<Utc>> {
         for field_name in field_names {
@@ -348,3 +371,86 @@ pub struct ScriptParameter {
     pub default_value: Option<String>,
     pub description: Option<String>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::content_type_definition;
+    use serde_json::json;
+
+    fn widgets() -> ContentTypeDefinition {
+        content_type_definition("xsiam", "widgets")
+    }
+
+    #[test]
+    fn test_fields_round_trip() {
+        let mut object = XsiamObject::new("7".to_string(), "Rule".to_string(), "biocs".to_string());
+        object.tenant_id = Some("tenant-a".to_string());
+        object.content.insert("severity".to_string(), json!("high"));
+
+        let fields = object.fields();
+        assert_eq!(fields, json!({"name": "Rule", "description": "", "tenant_id": "tenant-a", "severity": "high"}));
+        assert_eq!(object.with_fields(fields), object);
+    }
+
+    #[test]
+    fn test_fallback_id_is_derived_from_identity_fields() {
+        let widget = json!({"title": "Alerts", "type": "table", "creation_time": "n/a", "modification_time": 1700000000, "hits": 3});
+        let first = XsiamObject::from_api_response(&widget, &widgets()).unwrap();
+        assert!(first.has_fallback_id());
+        assert!(first.id.starts_with("widget_"));
+
+        // Timestamps and other content do not affect the ID, identity fields do
+        let repulled = json!({"title": "Alerts", "type": "table", "creation_time": "n/a", "modification_time": 1800000000, "hits": 9});
+        assert_eq!(XsiamObject::from_api_response(&repulled, &widgets()).unwrap().id, first.id);
+        let renamed = json!({"title": "Incidents", "type": "table", "creation_time": "n/a"});
+        assert_ne!(XsiamObject::from_api_response(&renamed, &widgets()).unwrap().id, first.id);
+
+        // Without identity fields the whole content is hashed
+        let untitled = json!({"creation_time": "n/a", "query": "a"});
+        let other = json!({"creation_time": "n/a", "query": "b"});
+        assert_ne!(
+            XsiamObject::from_api_response(&untitled, &widgets()).unwrap().id,
+            XsiamObject::from_api_response(&other, &widgets()).unwrap().id,
+        );
+    }
+
+    #[test]
+    fn test_fallback_id_collisions_get_distinct_ids() {
+        let parse = |json: Value| XsiamObject::from_api_response(&json, &widgets()).unwrap();
+        let mut objects = vec![
+            parse(json!({"title": "Alerts", "type": "table", "query": "a"})),
+            parse(json!({"title": "Alerts", "type": "table", "query": "b"})),
+            parse(json!({"title": "Alerts", "type": "table", "query": "b", "modification_time": 1700000000})),
+            parse(json!({"title": "Incidents", "type": "table"})),
+        ];
+        let shared = objects[0].id.clone();
+        let unique = objects[3].id.clone();
+        assert_eq!(objects[1].id, shared);
+
+        let collided = XsiamObject::disambiguate_fallback_ids(&mut objects);
+        assert_eq!(collided, vec![shared.clone()]);
+        assert!(objects[0].id.starts_with(&format!("{shared}_")));
+        assert_ne!(objects[0].id, objects[1].id);
+        // Identical apart from timestamps, so only the ordinal tells them apart
+        assert_eq!(objects[2].id, format!("{}_2", objects[1].id));
+        assert_eq!(objects[3].id, unique);
+
+        // The same pull gives the same IDs again
+        let mut repulled = vec![
+            parse(json!({"title": "Alerts", "type": "table", "query": "a"})),
+            parse(json!({"title": "Alerts", "type": "table", "query": "b"})),
+        ];
+        XsiamObject::disambiguate_fallback_ids(&mut repulled);
+        assert_eq!(repulled[0].id, objects[0].id);
+        assert_eq!(repulled[1].id, objects[1].id);
+    }
+
+    #[test]
+    fn test_platform_id_is_not_marked() {
+        let widget = json!({"title": "Alerts", "creation_time": 1700000000});
+        let object = XsiamObject::from_api_response(&widget, &widgets()).unwrap();
+        assert_eq!(object.id, "1700000000");
+        assert!(!object.has_fallback_id());
+    }
+}